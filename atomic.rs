@@ -0,0 +1,123 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Bindings for many threads dispatching while one thread
+//! occasionally changes them. Requires the `arc-swap`
+//! feature.
+//!
+//! Dispatch loads the current snapshot of the bindings
+//! without locking, so readers never wait for each other or
+//! for a writer. An update copies the snapshot, changes the
+//! copy and swaps it in whole, so a dispatch sees either
+//! all of an update or none of it. Updates are serialized
+//! by a lock, so none is lost.
+
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use arc_swap::ArcSwap;
+
+use SharedBindings;
+
+/// An `AtomicBindings` object holds a snapshot of
+/// `SharedBindings` that can be dispatched from many
+/// threads at once and replaced atomically.
+///
+/// # Examples:
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+/// use kbehdz::{AtomicBindings, SharedBindings};
+/// let mut kc = SharedBindings::new();
+/// kc.bind_fn("fire", || "fire");
+/// let kc = Arc::new(AtomicBindings::new(kc));
+/// let reader = Arc::clone(&kc);
+/// let fired = thread::spawn(move || reader.dispatch("fire"))
+///     .join()
+///     .unwrap();
+/// assert_eq!(fired, Some("fire"));
+/// kc.update(|b| {
+///     b.bind_fn("fire", || "big fire");
+///     Ok::<(), ()>(())
+/// }).unwrap();
+/// assert_eq!(kc.dispatch("fire"), Some("big fire"));
+/// ```
+pub struct AtomicBindings<E, R>
+    where E: Hash + Eq
+{
+    current: ArcSwap<SharedBindings<E, R>>,
+    writer: Mutex<()>,
+}
+
+impl<E, R> Default for AtomicBindings<E, R>
+    where E: Hash + Eq + Clone
+{
+    fn default() -> Self {
+        AtomicBindings::new(SharedBindings::new())
+    }
+}
+
+impl<E, R> AtomicBindings<E, R>
+    where E: Hash + Eq + Clone
+{
+    /// Start with `bindings` as the snapshot.
+    pub fn new(bindings: SharedBindings<E, R>) -> Self {
+        AtomicBindings {
+            current: ArcSwap::from_pointee(bindings),
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// The current snapshot. Later updates do not change
+    /// it.
+    pub fn snapshot(&self) -> Arc<SharedBindings<E, R>> {
+        self.current.load_full()
+    }
+
+    /// Given an event that is in the current snapshot, run
+    /// the corresponding action and return the result.
+    /// Return `None` if no such event is bound.
+    pub fn dispatch<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.current.load().run_action(event)
+    }
+
+    /// Change the bindings: `change` is given a copy of the
+    /// current snapshot to change, which replaces the
+    /// snapshot if `change` succeeds. If `change` fails,
+    /// the snapshot is left alone and the error returned,
+    /// so `change` can validate its result. Updates from
+    /// different threads run one at a time.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{AtomicBindings, SharedBindings};
+    /// let kc: AtomicBindings<String, u32> = AtomicBindings::default();
+    /// let result = kc.update(|b| {
+    ///     b.bind_fn("quit", || 0);
+    ///     if b.get_action("save").is_none() {
+    ///         return Err("no save binding");
+    ///     }
+    ///     Ok(())
+    /// });
+    /// assert_eq!(result, Err("no save binding"));
+    /// assert!(kc.dispatch("quit").is_none());
+    /// ```
+    pub fn update<F, X>(&self, change: F) -> Result<(), X>
+        where F: FnOnce(&mut SharedBindings<E, R>) -> Result<(), X>
+    {
+        // A panic in another update leaves the snapshot
+        // unchanged, so the poisoned lock is still good.
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut bindings = SharedBindings::clone(&self.current.load());
+        change(&mut bindings)?;
+        self.current.store(Arc::new(bindings));
+        Ok(())
+    }
+}
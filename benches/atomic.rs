@@ -0,0 +1,100 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Compare dispatch throughput of `AtomicBindings` against
+//! `RwLock<SharedBindings>` with 8 reader threads and a
+//! writer updating 10 times a second. Run with
+//! `cargo bench --features arc-swap --bench atomic`.
+
+extern crate kbehdz;
+
+use std::hint::black_box;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use kbehdz::{AtomicBindings, SharedBindings};
+
+const SIZE: usize = 100;
+const READERS: usize = 8;
+const RUN: Duration = Duration::from_secs(2);
+const WRITE_EVERY: Duration = Duration::from_millis(100);
+
+/// Run `READERS` threads calling `read` and one calling
+/// `write` every `WRITE_EVERY` for `RUN`, returning the
+/// number of reads.
+fn contend<D, W>(read: D, write: W) -> usize
+    where D: Fn(&str) -> Option<usize> + Send + Sync + 'static,
+          W: Fn(usize) + Send + 'static
+{
+    let read = Arc::new(read);
+    let stop = Arc::new(AtomicBool::new(false));
+    let reads = Arc::new(AtomicUsize::new(0));
+    let events: Arc<Vec<String>> =
+        Arc::new((0..SIZE).map(|i| format!("event-{}", i)).collect());
+    let readers: Vec<_> = (0..READERS).map(|_| {
+        let (read, stop, reads, events) =
+            (Arc::clone(&read), Arc::clone(&stop), Arc::clone(&reads), Arc::clone(&events));
+        thread::spawn(move || {
+            let mut count = 0;
+            while !stop.load(Ordering::Relaxed) {
+                black_box(read(&events[count % SIZE]));
+                count += 1;
+            }
+            reads.fetch_add(count, Ordering::Relaxed);
+        })
+    }).collect();
+    let writer = {
+        let stop = Arc::clone(&stop);
+        thread::spawn(move || {
+            let mut n = 0;
+            while !stop.load(Ordering::Relaxed) {
+                thread::sleep(WRITE_EVERY);
+                write(n);
+                n += 1;
+            }
+        })
+    };
+    thread::sleep(RUN);
+    stop.store(true, Ordering::Relaxed);
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    writer.join().unwrap();
+    reads.load(Ordering::Relaxed)
+}
+
+fn main() {
+    let mut kc = SharedBindings::new();
+    for i in 0..SIZE {
+        kc.bind_fn(format!("event-{}", i).as_str(), move || i);
+    }
+
+    let locked = Arc::new(RwLock::new(kc.clone()));
+    let reader = Arc::clone(&locked);
+    let rwlock_reads = contend(
+        move |event| reader.read().unwrap().run_action(event),
+        move |n| {
+            locked.write().unwrap().bind_fn("event-0", move || n);
+        },
+    );
+
+    let atomic = Arc::new(AtomicBindings::new(kc));
+    let reader = Arc::clone(&atomic);
+    let atomic_reads = contend(
+        move |event| reader.dispatch(event),
+        move |n| {
+            atomic.update(|b| {
+                b.bind_fn("event-0", move || n);
+                Ok::<(), ()>(())
+            }).unwrap();
+        },
+    );
+
+    let per_sec = |reads: usize| reads as f64 / RUN.as_secs_f64() / 1e6;
+    println!("{} readers: RwLock {:.2} M dispatches/s, AtomicBindings {:.2} M dispatches/s",
+             READERS, per_sec(rwlock_reads), per_sec(atomic_reads));
+}
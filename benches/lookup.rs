@@ -0,0 +1,54 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Compare event lookup in `Bindings` against a plain
+//! `HashMap` at several map sizes, to show where the
+//! small-map representation pays off. Run with
+//! `cargo bench`.
+
+extern crate kbehdz;
+
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::Instant;
+
+use kbehdz::{Action, Bindings};
+
+const LOOKUPS: u32 = 1_000_000;
+
+fn main() {
+    let act = || 1_u32;
+    for &size in &[2_u32, 8, 32, 512] {
+        let events: Vec<u32> = (0..size).collect();
+
+        let mut kc = Bindings::new();
+        let mut map: HashMap<u32, Action<u32>> = HashMap::new();
+        for event in &events {
+            kc.bind_action(event, &act);
+            map.insert(*event, &act);
+        }
+
+        let start = Instant::now();
+        let mut total = 0;
+        for i in 0..LOOKUPS {
+            total += kc.run_action(black_box(&(i % size))).unwrap();
+        }
+        let bindings_time = start.elapsed();
+        black_box(total);
+
+        let start = Instant::now();
+        let mut total = 0;
+        for i in 0..LOOKUPS {
+            total += map[black_box(&(i % size))]();
+        }
+        let hashmap_time = start.elapsed();
+        black_box(total);
+
+        println!("size {:4}: Bindings {:8.2} ns/lookup, HashMap {:8.2} ns/lookup",
+                 size,
+                 bindings_time.as_secs_f64() * 1e9 / f64::from(LOOKUPS),
+                 hashmap_time.as_secs_f64() * 1e9 / f64::from(LOOKUPS));
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Compare the cost of a one-binding "what if" change to
+//! 1000 bindings made with `PersistentBindings` against
+//! copying a `Bindings`. Run with `cargo bench`.
+
+extern crate kbehdz;
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use kbehdz::Bindings;
+
+const SIZE: usize = 1000;
+const CHANGES: usize = 10_000;
+
+fn main() {
+    let act = || 1_u32;
+    let other = || 2_u32;
+    let events: Vec<String> = (0..SIZE).map(|i| format!("event-{}", i)).collect();
+    let mut kc = Bindings::new();
+    for event in &events {
+        kc.bind_action(event.as_str(), &act);
+    }
+    let persistent = kc.to_persistent();
+
+    let start = Instant::now();
+    for i in 0..CHANGES {
+        let mut copy = Bindings::new();
+        for (event, action) in kc.to_vec() {
+            copy.bind_action(&event, action);
+        }
+        copy.bind_action(events[i % SIZE].as_str(), &other);
+        black_box(copy);
+    }
+    let copy_time = start.elapsed();
+
+    let start = Instant::now();
+    for i in 0..CHANGES {
+        black_box(persistent.with_binding(events[i % SIZE].as_str(), &other));
+    }
+    let persistent_time = start.elapsed();
+
+    println!("{} bindings: copy {:.2} us/change, persistent {:.2} us/change",
+             SIZE,
+             copy_time.as_secs_f64() * 1e6 / CHANGES as f64,
+             persistent_time.as_secs_f64() * 1e6 / CHANGES as f64);
+}
@@ -0,0 +1,187 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Cancellation of long-running actions, such as an export
+//! or a search, by a later event.
+//!
+//! A cancellable action is given a `CancelToken` each time
+//! it runs, which it or work it starts polls. The
+//! `CancelRegistry` remembers the tokens handed out for
+//! each event until their work is done, which is when every
+//! clone of the token but the registry's own is dropped.
+
+use std::borrow::{Borrow, ToOwned};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A flag telling an action, or work it started, to stop.
+/// Clones share the flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Make a token that is not cancelled.
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    /// Cancel the token and all its clones.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// True if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// True if no clones of the token are left but this
+    /// one.
+    fn is_done(&self) -> bool {
+        Arc::strong_count(&self.0) == 1
+    }
+}
+
+/// Type of cancellable actions with the given result type.
+pub type CancellableAction<'a, R> = &'a (dyn Fn(CancelToken) -> R + 'a);
+
+/// A `CancelRegistry` object binds events to cancellable
+/// actions, keeping the tokens of their outstanding runs so
+/// that they can be cancelled.
+///
+/// # Examples:
+///
+/// ```
+/// use std::sync::mpsc::channel;
+/// use std::thread::{self, JoinHandle};
+/// use kbehdz::{Bindings, CancelRegistry, CancelToken};
+/// let (started, wait_started) = channel();
+/// let export = move |token: CancelToken| -> JoinHandle<&str> {
+///     let started = started.clone();
+///     thread::spawn(move || {
+///         started.send(()).unwrap();
+///         while !token.is_cancelled() {
+///             thread::yield_now();
+///         }
+///         "export cancelled"
+///     })
+/// };
+/// let mut work = CancelRegistry::new();
+/// work.bind_action("C-e", &export);
+/// let cancel_all = || { work.cancel_all(); };
+/// let mut keys = Bindings::new();
+/// keys.bind_action("Escape", &cancel_all);
+///
+/// let job = work.run_action("C-e").unwrap();
+/// wait_started.recv().unwrap();
+/// assert_eq!(work.outstanding("C-e"), 1);
+/// keys.run_action("Escape");
+/// assert_eq!(job.join().unwrap(), "export cancelled");
+/// assert_eq!(work.outstanding("C-e"), 0);
+/// ```
+pub struct CancelRegistry<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    actions: HashMap<E, CancellableAction<'a, R>>,
+    tokens: RefCell<HashMap<E, Vec<CancelToken>>>,
+}
+
+impl<'a, E, R> Default for CancelRegistry<'a, E, R>
+    where E: Hash + Eq + Clone, R: 'a
+{
+    fn default() -> Self {
+        CancelRegistry::new()
+    }
+}
+
+impl<'a, E, R> CancelRegistry<'a, E, R>
+    where E: Hash + Eq + Clone, R: 'a
+{
+    /// Make a new empty registry.
+    pub fn new() -> Self {
+        CancelRegistry {
+            actions: HashMap::new(),
+            tokens: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Overwrite or create a binding. Runs of any previous
+    /// action for the event can still be cancelled.
+    pub fn bind_action<T>(&mut self, event: &T, action: CancellableAction<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.actions.insert(event.to_owned(), action);
+    }
+
+    /// Remove the binding for `event`, returning its
+    /// action. Its outstanding runs can still be
+    /// cancelled.
+    pub fn unbind_action<T>(&mut self, event: &T) -> Option<CancellableAction<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.actions.remove(event)
+    }
+
+    /// Given an event that is in the bindings, run the
+    /// corresponding action with a new token, remembering
+    /// the token, and return the result.  Return `None` if
+    /// no such event is bound.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        let (key, &action) = self.actions.get_key_value(event)?;
+        let token = CancelToken::new();
+        {
+            let mut tokens = self.tokens.borrow_mut();
+            let outstanding = tokens.entry(key.clone()).or_default();
+            outstanding.retain(|token| !token.is_done());
+            outstanding.push(token.clone());
+        }
+        Some(action(token))
+    }
+
+    /// Number of runs of the action for `event` whose work
+    /// is not yet done.
+    pub fn outstanding<T>(&self, event: &T) -> usize
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.tokens
+            .borrow()
+            .get(event)
+            .map_or(0, |tokens| tokens.iter().filter(|token| !token.is_done()).count())
+    }
+
+    /// Cancel the outstanding runs of the action for
+    /// `event`, returning how many there were.
+    pub fn cancel<T>(&self, event: &T) -> usize
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        let tokens = self.tokens.borrow_mut().remove(event);
+        cancel_tokens(tokens.into_iter().flatten())
+    }
+
+    /// Cancel every outstanding run, returning how many
+    /// there were.
+    pub fn cancel_all(&self) -> usize {
+        let tokens = std::mem::take(&mut *self.tokens.borrow_mut());
+        cancel_tokens(tokens.into_values().flatten())
+    }
+}
+
+/// Cancel the outstanding `tokens`, returning how many
+/// there were.
+fn cancel_tokens<I>(tokens: I) -> usize
+    where I: Iterator<Item=CancelToken>
+{
+    let mut count = 0;
+    for token in tokens.filter(|token| !token.is_done()) {
+        token.cancel();
+        count += 1;
+    }
+    count
+}
@@ -0,0 +1,254 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Functions building new actions from old ones.
+//!
+//! Each combinator takes anything callable as an action:
+//! a closure, a function, an `Action` reference or a boxed
+//! action. The result is boxed, ready for
+//! `OwnedBindings::bind_action()`, or for
+//! `Bindings::bind_action()` by reference.
+//!
+//! # Examples:
+//!
+//! ```
+//! use std::cell::RefCell;
+//! use std::rc::Rc;
+//! use kbehdz::{Bindings, OwnedBindings};
+//! use kbehdz::combinators::{map, seq};
+//! let log = Rc::new(RefCell::new(Vec::new()));
+//! let save = {
+//!     let log = Rc::clone(&log);
+//!     move || log.borrow_mut().push("saved")
+//! };
+//! let notify = {
+//!     let log = Rc::clone(&log);
+//!     move || log.borrow().len()
+//! };
+//! let mut kc = OwnedBindings::new();
+//! kc.bind_action("C-s", map(seq(save, notify), |n| format!("{} saved", n)));
+//! assert_eq!(kc.run_action("C-s").unwrap(), "1 saved");
+//! assert_eq!(kc.run_action("C-s").unwrap(), "2 saved");
+//!
+//! let bye = seq(|| (), || "bye");
+//! let mut kc = Bindings::new();
+//! kc.bind_action("q", &*bye);
+//! assert_eq!(kc.run_action("q"), Some("bye"));
+//! ```
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+/// Type of the actions made by combinators.
+pub type BoxedAction<'a, R> = Box<dyn Fn() -> R + 'a>;
+
+/// Make an action that runs `a` and then `b`, returning
+/// the result of `b`.
+pub fn seq<'a, A, B, X, R>(a: A, b: B) -> BoxedAction<'a, R>
+    where A: Fn() -> X + 'a, B: Fn() -> R + 'a
+{
+    Box::new(move || {
+        a();
+        b()
+    })
+}
+
+/// Make an action that runs `a` and passes its result
+/// through `f`.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::combinators::map;
+/// let len = map(|| "four", str::len);
+/// assert_eq!(len(), 4);
+/// ```
+pub fn map<'a, A, F, X, R>(a: A, f: F) -> BoxedAction<'a, R>
+    where A: Fn() -> X + 'a, F: Fn(X) -> R + 'a
+{
+    Box::new(move || f(a()))
+}
+
+/// Make an action that runs `a`, shows its result to `f`,
+/// and returns it.
+///
+/// # Examples:
+///
+/// ```
+/// use std::cell::Cell;
+/// use kbehdz::combinators::tap;
+/// let seen = Cell::new(0);
+/// let seven = tap(|| 7, |&n| seen.set(n));
+/// assert_eq!(seven(), 7);
+/// assert_eq!(seen.get(), 7);
+/// ```
+pub fn tap<'a, A, F, R>(a: A, f: F) -> BoxedAction<'a, R>
+    where A: Fn() -> R + 'a, F: Fn(&R) + 'a
+{
+    Box::new(move || {
+        let result = a();
+        f(&result);
+        result
+    })
+}
+
+/// Make an action that returns a clone of `value`.
+pub fn constant<'a, R>(value: R) -> BoxedAction<'a, R>
+    where R: Clone + 'a
+{
+    Box::new(move || value.clone())
+}
+
+/// Make an action that runs `a` if `pred` returns true when
+/// the action is run, and `b` otherwise.
+///
+/// # Examples:
+///
+/// ```
+/// use std::cell::Cell;
+/// use kbehdz::combinators::select;
+/// let paused = Cell::new(false);
+/// let space = select(|| paused.get(), || "resume", || "jump");
+/// assert_eq!(space(), "jump");
+/// paused.set(true);
+/// assert_eq!(space(), "resume");
+/// ```
+pub fn select<'a, P, A, B, R>(pred: P, a: A, b: B) -> BoxedAction<'a, R>
+    where P: Fn() -> bool + 'a, A: Fn() -> R + 'a, B: Fn() -> R + 'a
+{
+    Box::new(move || if pred() { a() } else { b() })
+}
+
+/// Make an action that runs `a` and discards its result.
+pub fn ignore<'a, A, X>(a: A) -> BoxedAction<'a, ()>
+    where A: Fn() -> X + 'a
+{
+    Box::new(move || {
+        a();
+    })
+}
+
+/// How long to wait before retrying a failed action.
+pub enum Backoff {
+    /// Retry at once.
+    Immediate,
+    /// Wait the same time before each retry.
+    Fixed(Duration),
+    /// Wait `initial` before the first retry, doubling the
+    /// wait for each retry after that, up to `max`.
+    Exponential {
+        /// Wait before the first retry.
+        initial: Duration,
+        /// Longest wait.
+        max: Duration,
+    },
+    /// Wait as long as the function says, given the number
+    /// of the retry starting from 1. This is for jitter and
+    /// other policies.
+    Custom(Box<dyn Fn(u32) -> Duration>),
+}
+
+impl Backoff {
+    /// Wait before retry number `retry`, starting from 1.
+    pub fn delay(&self, retry: u32) -> Duration {
+        match *self {
+            Backoff::Immediate => Duration::from_secs(0),
+            Backoff::Fixed(wait) => wait,
+            Backoff::Exponential { initial, max } => {
+                let factor = 1_u32.checked_shl(retry.saturating_sub(1)).unwrap_or(u32::MAX);
+                initial.checked_mul(factor).unwrap_or(max).min(max)
+            },
+            Backoff::Custom(ref delay) => delay(retry),
+        }
+    }
+}
+
+/// When and how often to retry a failed action.
+pub struct RetryPolicy {
+    /// Most times to run the action, including the first.
+    /// Zero is treated as one.
+    pub attempts: u32,
+    /// Wait between attempts.
+    pub backoff: Backoff,
+}
+
+/// Error from an action made by `with_retry()` when every
+/// attempt failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryError<X> {
+    /// Number of attempts made.
+    pub attempts: u32,
+    /// Error of the last attempt.
+    pub error: X,
+}
+
+impl<X: Display> Display for RetryError<X> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "failed after {} attempts: {}", self.attempts, self.error)
+    }
+}
+
+impl<X: Error + 'static> Error for RetryError<X> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Make an action that runs `action`, running it again
+/// when it fails as `policy` says. Waits are done by
+/// calling `sleep`, which would usually be
+/// `std::thread::sleep`.
+///
+/// # Examples:
+///
+/// ```
+/// use std::cell::{Cell, RefCell};
+/// use std::time::Duration;
+/// use kbehdz::combinators::{with_retry, Backoff, RetryPolicy};
+/// let tries = Cell::new(0);
+/// let reconnect = || {
+///     tries.set(tries.get() + 1);
+///     if tries.get() < 3 { Err("timeout") } else { Ok("connected") }
+/// };
+/// let waits = RefCell::new(Vec::new());
+/// let policy = RetryPolicy {
+///     attempts: 4,
+///     backoff: Backoff::Exponential {
+///         initial: Duration::from_millis(100),
+///         max: Duration::from_secs(1),
+///     },
+/// };
+/// let retrying = with_retry(&reconnect, policy, |d| waits.borrow_mut().push(d));
+/// assert_eq!(retrying(), Ok("connected"));
+/// assert_eq!(*waits.borrow(), vec![Duration::from_millis(100),
+///                                  Duration::from_millis(200)]);
+///
+/// let fail = || Err::<(), _>("down");
+/// let policy = RetryPolicy { attempts: 2, backoff: Backoff::Immediate };
+/// let err = with_retry(fail, policy, |_| ())().unwrap_err();
+/// assert_eq!(err.to_string(), "failed after 2 attempts: down");
+/// ```
+pub fn with_retry<'a, A, S, T, X>(action: A, policy: RetryPolicy, sleep: S)
+                                  -> BoxedAction<'a, Result<T, RetryError<X>>>
+    where A: Fn() -> Result<T, X> + 'a, S: Fn(Duration) + 'a
+{
+    let attempts = policy.attempts.max(1);
+    Box::new(move || {
+        let mut attempt = 1;
+        loop {
+            match action() {
+                Ok(value) => return Ok(value),
+                Err(error) if attempt >= attempts => {
+                    return Err(RetryError { attempts: attempt, error });
+                },
+                Err(_) => {
+                    sleep(policy.backoff.delay(attempt));
+                    attempt += 1;
+                },
+            }
+        }
+    })
+}
@@ -0,0 +1,169 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Dead keys and compose sequences for character events:
+//! `´` then `e` gives `é`. A `Compose` sits in front of a
+//! `Bindings<char, R>`, turning the characters typed into
+//! the characters meant.
+
+use std::collections::{HashMap, HashSet};
+
+/// Default dead keys, each with the letters it combines
+/// with and the combined letters, in the same order.
+const DEFAULT_TABLE: &[(char, &str, &str)] = &[
+    ('´', "aeiouyAEIOUY", "áéíóúýÁÉÍÓÚÝ"),
+    ('`', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+    ('^', "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+    ('¨', "aeiouyAEIOU", "äëïöüÿÄËÏÖÜ"),
+    ('~', "anoANO", "ãñõÃÑÕ"),
+    ('¸', "cC", "çÇ"),
+];
+
+/// What `Compose::feed()` made of a character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComposeResult {
+    /// The character may continue a sequence: wait for
+    /// more.
+    Pending,
+    /// A sequence was completed, giving this character.
+    Composed(char),
+    /// No sequence matched: these characters should be
+    /// handled as typed, in order.
+    Passthrough(Vec<char>),
+}
+
+/// A `Compose` object turns compose sequences in a stream
+/// of characters into single characters. Characters that
+/// turn out not to be part of a sequence are passed
+/// through, in order, so none are lost.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{Compose, ComposeResult};
+/// let mut compose = Compose::default();
+/// assert_eq!(compose.feed('´'), ComposeResult::Pending);
+/// assert_eq!(compose.feed('e'), ComposeResult::Composed('é'));
+/// assert_eq!(compose.feed('x'), ComposeResult::Passthrough(vec!['x']));
+/// // An aborted sequence gives back what it swallowed.
+/// assert_eq!(compose.feed('~'), ComposeResult::Pending);
+/// assert_eq!(compose.feed('z'), ComposeResult::Passthrough(vec!['~', 'z']));
+/// // A dead key followed by space gives the dead key.
+/// compose.feed('^');
+/// assert_eq!(compose.feed(' '), ComposeResult::Composed('^'));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Compose {
+    sequences: HashMap<Vec<char>, char>,
+    prefixes: HashSet<Vec<char>>,
+    pending: Vec<char>,
+}
+
+impl Compose {
+    /// Make a `Compose` with no sequences. `default()`
+    /// instead gives one with the default dead keys, for
+    /// acute, grave, circumflex, diaeresis, tilde and
+    /// cedilla, each also giving itself when followed by a
+    /// space.
+    pub fn new() -> Self {
+        Compose {
+            sequences: HashMap::new(),
+            prefixes: HashSet::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Add the sequence `sequence`, giving `result`,
+    /// replacing any existing one. A sequence that starts
+    /// with a complete shorter sequence can never be
+    /// completed, since the shorter one wins.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sequence` is empty.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Compose, ComposeResult};
+    /// let mut compose = Compose::new();
+    /// compose.add("oe", 'œ');
+    /// compose.add("<<", '«');
+    /// compose.feed('o');
+    /// assert_eq!(compose.feed('e'), ComposeResult::Composed('œ'));
+    /// compose.feed('<');
+    /// assert_eq!(compose.feed('<'), ComposeResult::Composed('«'));
+    /// ```
+    pub fn add(&mut self, sequence: &str, result: char) {
+        let sequence: Vec<char> = sequence.chars().collect();
+        assert!(!sequence.is_empty(), "empty compose sequence");
+        for end in 1..sequence.len() {
+            self.prefixes.insert(sequence[..end].to_vec());
+        }
+        self.sequences.insert(sequence, result);
+    }
+
+    /// The characters of an unfinished sequence.
+    pub fn pending(&self) -> &[char] {
+        &self.pending
+    }
+
+    /// Give up on any unfinished sequence, returning its
+    /// characters to be handled as typed. This is for when
+    /// input pauses or ends mid-sequence.
+    pub fn flush(&mut self) -> Vec<char> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Feed the next character typed.
+    ///
+    /// If a character aborts a sequence but may itself
+    /// start one, as a second dead key does, the characters
+    /// before it are passed through and it is kept pending.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Compose, ComposeResult};
+    /// let mut compose = Compose::default();
+    /// compose.feed('´');
+    /// assert_eq!(compose.feed('¨'), ComposeResult::Passthrough(vec!['´']));
+    /// assert_eq!(compose.feed('u'), ComposeResult::Composed('ü'));
+    /// compose.feed('`');
+    /// assert_eq!(compose.flush(), vec!['`']);
+    /// assert!(compose.pending().is_empty());
+    /// ```
+    pub fn feed(&mut self, c: char) -> ComposeResult {
+        self.pending.push(c);
+        if let Some(&result) = self.sequences.get(&self.pending) {
+            self.pending.clear();
+            return ComposeResult::Composed(result);
+        }
+        if self.prefixes.contains(&self.pending) {
+            return ComposeResult::Pending;
+        }
+        // Abort the sequence, restarting with `c` if it can
+        // start one.
+        if self.pending.len() > 1 && self.prefixes.contains(&[c][..]) {
+            self.pending.pop();
+            let swallowed = std::mem::replace(&mut self.pending, vec![c]);
+            return ComposeResult::Passthrough(swallowed);
+        }
+        ComposeResult::Passthrough(self.flush())
+    }
+}
+
+impl Default for Compose {
+    fn default() -> Self {
+        let mut compose = Compose::new();
+        for &(dead, bases, composed) in DEFAULT_TABLE {
+            for (base, result) in bases.chars().zip(composed.chars()) {
+                compose.add(&format!("{}{}", dead, base), result);
+            }
+            compose.add(&format!("{} ", dead), dead);
+        }
+        compose
+    }
+}
@@ -0,0 +1,233 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Versioned binding configuration files, upgraded from
+//! older versions on load.
+//!
+//! A saved file is a JSON object with a `version` number
+//! and the `config` itself. Loading an older version runs
+//! the registered migrations on the JSON value, one after
+//! another, until it reaches the current version, so that
+//! migrations do not need the old Rust types.
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// A migration from one version of a config to a later
+/// one, failing with a message.
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+/// Error from loading a versioned config.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file, or the config after migration, is not
+    /// valid.
+    Json(serde_json::Error),
+    /// The file has no `version` number.
+    MissingVersion,
+    /// The file's `version` number is too large to be any
+    /// version.
+    BadVersion(u64),
+    /// The file is from a later version than this program
+    /// knows.
+    FutureVersion {
+        /// The version of the file.
+        found: u32,
+        /// The current version.
+        current: u32,
+    },
+    /// No migration is registered from this version.
+    NoMigration(u32),
+    /// A migration failed.
+    Migration {
+        /// The version migrated from.
+        from: u32,
+        /// The version migrated to.
+        to: u32,
+        /// The message the migration failed with.
+        message: String,
+    },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::Io(ref e) => write!(f, "{}", e),
+            ConfigError::Json(ref e) => write!(f, "{}", e),
+            ConfigError::MissingVersion => write!(f, "config has no version"),
+            ConfigError::BadVersion(version) =>
+                write!(f, "config version {} is out of range", version),
+            ConfigError::FutureVersion { found, current } =>
+                write!(f, "config version {} is newer than supported version {}",
+                       found, current),
+            ConfigError::NoMigration(from) =>
+                write!(f, "no migration from config version {}", from),
+            ConfigError::Migration { from, to, ref message } =>
+                write!(f, "migrating config from version {} to {}: {}", from, to, message),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ConfigError::Io(ref e) => Some(e),
+            ConfigError::Json(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(e: serde_json::Error) -> Self {
+        ConfigError::Json(e)
+    }
+}
+
+/// A `ConfigSchema` object saves configs with the current
+/// version and loads configs of that or any earlier version
+/// it has migrations for.
+///
+/// # Examples:
+///
+/// ```
+/// extern crate serde_json;
+/// # extern crate kbehdz;
+/// use serde_json::Value;
+/// use kbehdz::{ConfigError, ConfigSchema};
+/// # fn main() {
+/// fn add_quit(mut config: Value) -> Result<Value, String> {
+///     config["quit"] = "C-q".into();
+///     Ok(config)
+/// }
+/// let mut schema = ConfigSchema::new(2);
+/// schema.register_migration(1, 2, add_quit);
+/// let v1 = r#"{"version": 1, "config": {"save": "C-s"}}"#;
+/// let config: Value = schema.read_from(v1.as_bytes()).unwrap();
+/// assert_eq!(config["quit"], "C-q");
+///
+/// let mut saved = Vec::new();
+/// schema.write_to(&config, &mut saved).unwrap();
+/// assert_eq!(schema.read_from::<Value, _>(&saved[..]).unwrap(), config);
+///
+/// let v3 = r#"{"version": 3, "config": {}}"#;
+/// match schema.read_from::<Value, _>(v3.as_bytes()) {
+///     Err(ConfigError::FutureVersion { found: 3, current: 2 }) => (),
+///     r => panic!("unexpected {:?}", r),
+/// }
+/// # }
+/// ```
+pub struct ConfigSchema {
+    current: u32,
+    migrations: BTreeMap<u32, (u32, Migration)>,
+}
+
+impl ConfigSchema {
+    /// Make a schema whose current version is `current`,
+    /// with no migrations.
+    pub fn new(current: u32) -> Self {
+        ConfigSchema {
+            current,
+            migrations: BTreeMap::new(),
+        }
+    }
+
+    /// The version configs are saved with.
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+
+    /// Upgrade configs of version `from` to version `to`
+    /// with `migration`, replacing any migration from
+    /// `from`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `to` is not later than `from`, or is later
+    /// than the current version.
+    pub fn register_migration(&mut self, from: u32, to: u32, migration: Migration) {
+        assert!(from < to && to <= self.current,
+                "bad migration from version {} to {}", from, to);
+        self.migrations.insert(from, (to, migration));
+    }
+
+    /// Upgrade `config` from version `version` to the
+    /// current version.
+    pub fn migrate(&self, mut version: u32, mut config: Value) -> Result<Value, ConfigError> {
+        if version > self.current {
+            return Err(ConfigError::FutureVersion { found: version, current: self.current });
+        }
+        while version < self.current {
+            let &(to, migration) = self.migrations
+                .get(&version)
+                .ok_or(ConfigError::NoMigration(version))?;
+            config = migration(config)
+                .map_err(|message| ConfigError::Migration { from: version, to, message })?;
+            version = to;
+        }
+        Ok(config)
+    }
+
+    /// Write `config` as JSON with the current version.
+    pub fn write_to<T, W>(&self, config: &T, mut writer: W) -> io::Result<()>
+        where T: Serialize, W: Write
+    {
+        let envelope = json!({ "version": self.current, "config": config });
+        serde_json::to_writer_pretty(&mut writer, &envelope)?;
+        writer.write_all(b"\n")?;
+        writer.flush()
+    }
+
+    /// Save `config` with the current version in the file
+    /// at `path`.
+    pub fn save<T, P>(&self, config: &T, path: P) -> io::Result<()>
+        where T: Serialize, P: AsRef<Path>
+    {
+        self.write_to(config, BufWriter::new(File::create(path)?))
+    }
+
+    /// Read a config written by `write_to()` with this or
+    /// an earlier version, migrating it to the current
+    /// version.
+    pub fn read_from<T, B>(&self, reader: B) -> Result<T, ConfigError>
+        where T: DeserializeOwned, B: Read
+    {
+        let mut envelope: Value = serde_json::from_reader(reader)?;
+        let version = envelope
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or(ConfigError::MissingVersion)?;
+        let version = u32::try_from(version).map_err(|_| ConfigError::BadVersion(version))?;
+        let config = envelope.get_mut("config").map(Value::take).unwrap_or(Value::Null);
+        let config = self.migrate(version, config)?;
+        Ok(serde_json::from_value(config)?)
+    }
+
+    /// Load a config saved by `save()` with this or an
+    /// earlier version, migrating it to the current
+    /// version.
+    pub fn load<T, P>(&self, path: P) -> Result<T, ConfigError>
+        where T: DeserializeOwned, P: AsRef<Path>
+    {
+        self.read_from(BufReader::new(File::open(path)?))
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Bindings that apply only while named contexts are
+//! active, as with the "when" clauses of editor keymaps.
+//! One map covers every combination of UI states.
+
+use std::borrow::{Borrow, ToOwned};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::iter::FromIterator;
+
+use {Action, Bindings};
+
+/// A set of active context names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContextSet(HashSet<String>);
+
+impl ContextSet {
+    /// Make a new set with no active contexts.
+    pub fn new() -> Self {
+        ContextSet::default()
+    }
+
+    /// Make `context` active.
+    pub fn activate(&mut self, context: &str) {
+        self.0.insert(context.to_owned());
+    }
+
+    /// Make `context` inactive.
+    pub fn deactivate(&mut self, context: &str) {
+        self.0.remove(context);
+    }
+
+    /// True if `context` is active.
+    pub fn contains(&self, context: &str) -> bool {
+        self.0.contains(context)
+    }
+}
+
+impl<'c> FromIterator<&'c str> for ContextSet {
+    fn from_iter<I: IntoIterator<Item=&'c str>>(iter: I) -> Self {
+        ContextSet(iter.into_iter().map(str::to_owned).collect())
+    }
+}
+
+/// A `ContextBindings` object binds events to actions
+/// either generically or within a named context. When an
+/// event is dispatched, a binding whose context is active
+/// wins over the generic binding; if several are active,
+/// the one bound most recently wins.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{ContextBindings, ContextSet};
+/// let save = || "save";
+/// let close_panel = || "close panel";
+/// let mut kc = ContextBindings::new();
+/// kc.bind_action("Esc", &save);
+/// kc.bind_in_context("Esc", "panelOpen", &close_panel);
+/// let mut active: ContextSet = vec!["editorFocused"].into_iter().collect();
+/// assert_eq!(kc.dispatch_in("Esc", &active), Some("save"));
+/// active.activate("panelOpen");
+/// assert_eq!(kc.dispatch_in("Esc", &active), Some("close panel"));
+/// assert!(kc.dispatch_in("F1", &active).is_none());
+/// ```
+pub struct ContextBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    generic: Bindings<'a, E, R>,
+    contextual: HashMap<E, Vec<(String, Action<'a, R>)>>,
+}
+
+impl<'a, E, R> Default for ContextBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    fn default() -> Self {
+        ContextBindings::new()
+    }
+}
+
+impl<'a, E, R> ContextBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Make a new empty binding.
+    pub fn new() -> Self {
+        ContextBindings {
+            generic: Bindings::new(),
+            contextual: HashMap::new(),
+        }
+    }
+
+    /// The generic bindings.
+    pub fn generic(&self) -> &Bindings<'a, E, R> {
+        &self.generic
+    }
+
+    /// Overwrite or create a generic binding, which applies
+    /// in every context.
+    pub fn bind_action<T>(&mut self, event: &T, action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.generic.bind_action(event, action);
+    }
+
+    /// Overwrite or create a binding that applies only
+    /// while `context` is active.
+    pub fn bind_in_context<T>(&mut self, event: &T, context: &str, action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        let entries = self.contextual.entry(event.to_owned()).or_default();
+        entries.retain(|(c, _)| c != context);
+        entries.push((context.to_owned(), action));
+    }
+
+    /// Remove the generic binding for `event`, returning
+    /// its action.
+    pub fn unbind_action<T>(&mut self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.generic.unbind_action(event)
+    }
+
+    /// Remove the binding for `event` in `context`,
+    /// returning its action.
+    pub fn unbind_in_context<T>(&mut self, event: &T, context: &str)
+                                -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        let entries = self.contextual.get_mut(event)?;
+        let posn = entries.iter().position(|(c, _)| c == context)?;
+        let (_, action) = entries.remove(posn);
+        if entries.is_empty() {
+            self.contextual.remove(event);
+        }
+        Some(action)
+    }
+
+    /// Return the action for `event` given the `active`
+    /// contexts, unexecuted. Return `None` if nothing
+    /// applies.
+    pub fn get_action_in<T>(&self, event: &T, active: &ContextSet) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        let contextual = self.contextual.get(event).and_then(|entries| {
+            entries
+                .iter()
+                .rev()
+                .find(|(context, _)| active.contains(context))
+                .map(|&(_, action)| action)
+        });
+        contextual.or_else(|| self.generic.get_action(event))
+    }
+
+    /// Run the action for `event` given the `active`
+    /// contexts and return the result. Return `None` if
+    /// nothing applies.
+    pub fn dispatch_in<T>(&self, event: &T, active: &ContextSet) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.get_action_in(event, active).map(|action| action())
+    }
+}
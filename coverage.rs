@@ -0,0 +1,103 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Checking bindings against the full set of events that
+//! ought to be bound, typically in a test.
+
+use std::collections::HashSet;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+use Bindings;
+
+/// How well some bindings cover a universe of events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage<E> {
+    /// Events of the universe that are bound, in universe
+    /// order.
+    pub bound: Vec<E>,
+    /// Events of the universe that are not bound, in
+    /// universe order.
+    pub unbound: Vec<E>,
+    /// Bound events that are not in the universe, in no
+    /// particular order.
+    pub extra: Vec<E>,
+}
+
+impl<E> Coverage<E> {
+    /// True if every event of the universe is bound.
+    /// Extra events do not count against completeness.
+    pub fn is_complete(&self) -> bool {
+        self.unbound.is_empty()
+    }
+}
+
+impl<E: Debug> fmt::Display for Coverage<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total = self.bound.len() + self.unbound.len();
+        write!(f, "{} of {} events bound", self.bound.len(), total)?;
+        if !self.unbound.is_empty() {
+            write!(f, "; unbound: {:?}", self.unbound)?;
+        }
+        if !self.extra.is_empty() {
+            write!(f, "; extra: {:?}", self.extra)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, E, R> Bindings<'a, E, R>
+    where E: Hash + Eq + Clone, R: 'a
+{
+    /// Report which events of `universe` are bound and which
+    /// are not, along with any bound events outside the
+    /// universe.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    /// enum GameKey { Jump, Fire, Pause, Debug }
+    /// const ALL: &[GameKey] = &[GameKey::Jump, GameKey::Fire, GameKey::Pause];
+    /// let act = || ();
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action(&GameKey::Jump, &act);
+    /// kc.bind_action(&GameKey::Pause, &act);
+    /// kc.bind_action(&GameKey::Debug, &act);
+    /// let coverage = kc.coverage(ALL.iter().cloned());
+    /// assert!(!coverage.is_complete());
+    /// assert_eq!(coverage.unbound, vec![GameKey::Fire]);
+    /// assert_eq!(coverage.to_string(),
+    ///            "2 of 3 events bound; unbound: [Fire]; extra: [Debug]");
+    /// kc.bind_action(&GameKey::Fire, &act);
+    /// let coverage = kc.coverage(ALL.iter().cloned());
+    /// assert!(coverage.is_complete(), "{}", coverage);
+    /// ```
+    pub fn coverage<I>(&self, universe: I) -> Coverage<E>
+        where I: IntoIterator<Item=E>
+    {
+        let mut seen = HashSet::new();
+        let mut bound = Vec::new();
+        let mut unbound = Vec::new();
+        for event in universe {
+            if seen.contains(&event) {
+                continue;
+            }
+            seen.insert(event.clone());
+            if self.store.contains_key(&event) {
+                bound.push(event);
+            } else {
+                unbound.push(event);
+            }
+        }
+        let extra = self.store
+            .keys()
+            .filter(|&event| !seen.contains(event))
+            .cloned()
+            .collect();
+        Coverage { bound, unbound, extra }
+    }
+}
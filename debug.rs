@@ -0,0 +1,99 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Bindings that remember where in the source each binding
+//! was made, for tracking down unexpected bindings in large
+//! keymap setups.
+
+use std::borrow::{Borrow, ToOwned};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::panic::Location;
+
+use {Action, Bindings};
+
+/// A `DebugBindings` object wraps a `Bindings`, recording
+/// the source location of the call that made each binding.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{Bindings, DebugBindings};
+/// let fire = || "fire";
+/// let mut kc = DebugBindings::new(Bindings::new());
+/// let line = line!(); kc.bind_action(&'f', &fire);
+/// let site = kc.binding_site(&'f').unwrap();
+/// assert_eq!(site.line(), line);
+/// assert_eq!(site.file(), file!());
+/// assert_eq!(kc.run_action(&'f'), Some("fire"));
+/// kc.unbind_action(&'f');
+/// assert!(kc.binding_site(&'f').is_none());
+/// ```
+pub struct DebugBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    bindings: Bindings<'a, E, R>,
+    sites: HashMap<E, &'static Location<'static>>,
+}
+
+impl<'a, E, R> DebugBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Start recording binding sites for `bindings`. Its
+    /// existing bindings have no recorded site.
+    pub fn new(bindings: Bindings<'a, E, R>) -> Self {
+        DebugBindings {
+            bindings,
+            sites: HashMap::new(),
+        }
+    }
+
+    /// The wrapped bindings.
+    pub fn bindings(&self) -> &Bindings<'a, E, R> {
+        &self.bindings
+    }
+
+    /// Stop recording, returning the bindings.
+    pub fn into_inner(self) -> Bindings<'a, E, R> {
+        self.bindings
+    }
+
+    /// Overwrite or create a binding, recording the
+    /// location of the caller as its site.
+    #[track_caller]
+    pub fn bind_action<T>(&mut self, event: &T, action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.bindings.bind_action(event, action);
+        self.sites.insert(event.to_owned(), Location::caller());
+    }
+
+    /// Remove the binding for `event` and its site,
+    /// returning its action.
+    pub fn unbind_action<T>(&mut self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.sites.remove(event);
+        self.bindings.unbind_action(event)
+    }
+
+    /// Where the binding for `event` was made. Return
+    /// `None` if `event` is unbound or was bound before
+    /// recording started.
+    pub fn binding_site<T>(&self, event: &T) -> Option<&'static Location<'static>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.sites.get(event).cloned()
+    }
+
+    /// Given an event that is in the bindings, run the
+    /// corresponding action and return the result.  Return
+    /// `None` if no such event is bound.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.bindings.run_action(event)
+    }
+}
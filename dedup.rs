@@ -0,0 +1,133 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Suppression of events delivered twice in a row, as some
+//! terminal backends occasionally do, which would make
+//! toggling actions flicker.
+
+use std::borrow::{Borrow, ToOwned};
+use std::cell::RefCell;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use {Action, Bindings};
+
+/// What `DedupBindings::dispatch_at()` did with an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deduped<R> {
+    /// The action ran, with this result.
+    Ran(R),
+    /// The event is not bound.
+    Unbound,
+    /// The event repeated the previous one within the
+    /// window, so nothing ran.
+    Suppressed,
+}
+
+/// A `DedupBindings` object wraps a `Bindings`, optionally
+/// suppressing a dispatch of the same event as the previous
+/// dispatch within a time window. Only the last event and
+/// its time are remembered, so this is cheap, and applies
+/// to the map as a whole rather than per event. Every
+/// dispatch, even a suppressed one, becomes the previous
+/// one for the next.
+///
+/// # Examples:
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use kbehdz::{Bindings, DedupBindings, Deduped};
+/// let toggle = || "toggled";
+/// let other = || "other";
+/// let mut kc = Bindings::new();
+/// kc.bind_action(&'t', &toggle);
+/// kc.bind_action(&'o', &other);
+/// let mut kc = DedupBindings::new(kc);
+/// kc.set_dedup_window(Duration::from_millis(20));
+/// let t = Instant::now();
+/// let ms = |n| t + Duration::from_millis(n);
+/// assert_eq!(kc.dispatch_at(&'t', ms(0)), Deduped::Ran("toggled"));
+/// assert_eq!(kc.dispatch_at(&'t', ms(5)), Deduped::Suppressed);
+/// assert_eq!(kc.dispatch_at(&'t', ms(100)), Deduped::Ran("toggled"));
+/// assert_eq!(kc.dispatch_at(&'o', ms(101)), Deduped::Ran("other"));
+/// assert_eq!(kc.dispatch_at(&'t', ms(102)), Deduped::Ran("toggled"));
+/// assert_eq!(kc.dispatch_at(&'x', ms(103)), Deduped::Unbound);
+/// kc.clear_dedup_window();
+/// assert_eq!(kc.dispatch_at(&'t', ms(104)), Deduped::Ran("toggled"));
+/// assert_eq!(kc.dispatch_at(&'t', ms(104)), Deduped::Ran("toggled"));
+/// ```
+pub struct DedupBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    bindings: Bindings<'a, E, R>,
+    window: Option<Duration>,
+    last: RefCell<Option<(E, Instant)>>,
+}
+
+impl<'a, E, R> DedupBindings<'a, E, R>
+    where E: Hash + Eq + Clone, R: 'a
+{
+    /// Wrap `bindings`, with no window: nothing is
+    /// suppressed until `set_dedup_window()`.
+    pub fn new(bindings: Bindings<'a, E, R>) -> Self {
+        DedupBindings {
+            bindings,
+            window: None,
+            last: RefCell::new(None),
+        }
+    }
+
+    /// The wrapped bindings.
+    pub fn bindings(&self) -> &Bindings<'a, E, R> {
+        &self.bindings
+    }
+
+    /// Stop suppressing, returning the bindings.
+    pub fn into_inner(self) -> Bindings<'a, E, R> {
+        self.bindings
+    }
+
+    /// Suppress repeats of the previous event less than
+    /// `window` after it.
+    pub fn set_dedup_window(&mut self, window: Duration) {
+        self.window = Some(window);
+    }
+
+    /// Stop suppressing repeats.
+    pub fn clear_dedup_window(&mut self) {
+        self.window = None;
+    }
+
+    /// Overwrite or create a binding.
+    pub fn bind_action<T>(&mut self, event: &T, action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.bindings.bind_action(event, action);
+    }
+
+    /// Dispatch `event`, which happened at time `now`,
+    /// running its action unless it is suppressed as a
+    /// repeat.
+    pub fn dispatch_at(&self, event: &E, now: Instant) -> Deduped<R> {
+        let repeat = {
+            let mut last = self.last.borrow_mut();
+            let repeat = match (self.window, &*last) {
+                (Some(window), Some((previous, then))) => {
+                    previous == event && now.duration_since(*then) < window
+                },
+                _ => false,
+            };
+            *last = Some((event.clone(), now));
+            repeat
+        };
+        if repeat {
+            return Deduped::Suppressed;
+        }
+        match self.bindings.run_action::<E>(event) {
+            Some(result) => Deduped::Ran(result),
+            None => Deduped::Unbound,
+        }
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Changes between binding sets, with actions named by an
+//! `ActionRegistry`, for syncing bindings between machines
+//! by sending only what changed.
+//!
+//! With the `serde` feature, deltas can be serialized.
+
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use {ActionRegistry, Bindings};
+
+/// The changes taking one binding set to another. Each
+/// action is given by its registered name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BindingsDelta<E> {
+    /// Events newly bound, with their actions.
+    pub added: Vec<(E, String)>,
+    /// Events no longer bound, with their old actions.
+    pub removed: Vec<(E, String)>,
+    /// Events bound to a different action, with the old
+    /// and new actions.
+    pub changed: Vec<(E, String, String)>,
+}
+
+impl<E> Default for BindingsDelta<E> {
+    fn default() -> Self {
+        BindingsDelta {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        }
+    }
+}
+
+impl<E> BindingsDelta<E> {
+    /// True if the delta changes nothing.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Error from making or applying a `BindingsDelta`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeltaError<E> {
+    /// The action bound to this event has no registered
+    /// name, so it cannot be put in a delta.
+    Unnamed(E),
+    /// No action is registered under this name.
+    UnknownAction(String),
+    /// The binding of `event` has diverged: its action,
+    /// named `found`, is neither the one the delta changes
+    /// from nor the one it changes to.
+    Conflict {
+        /// The event whose binding has diverged.
+        event: E,
+        /// The name of its action, or `None` if it is
+        /// unbound or its action has no registered name.
+        found: Option<String>,
+    },
+}
+
+impl<E: Debug> Display for DeltaError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DeltaError::Unnamed(ref event) =>
+                write!(f, "action bound to {:?} has no name", event),
+            DeltaError::UnknownAction(ref name) => write!(f, "unknown action {:?}", name),
+            DeltaError::Conflict { ref event, found: Some(ref found) } =>
+                write!(f, "{:?} is bound to {:?}, which the delta does not expect",
+                       event, found),
+            DeltaError::Conflict { ref event, found: None } =>
+                write!(f, "{:?} is bound to an unnamed action or unbound, \
+                           which the delta does not expect", event),
+        }
+    }
+}
+
+impl<E: Debug> Error for DeltaError<E> {}
+
+impl<'a, E, R> Bindings<'a, E, R>
+    where E: Hash + Eq + Clone, R: 'a
+{
+    /// Name of the action bound to `event` in `registry`.
+    fn action_name<'r>(&self, event: &E, registry: &'r ActionRegistry<'a, R>)
+                       -> Option<&'r str>
+    {
+        self.store.get::<E>(event).and_then(|&action| registry.name_of(action))
+    }
+
+    /// Return the changes that take `base` to these
+    /// bindings, naming actions by `registry`. Return an
+    /// error for each bound action, in either, that has no
+    /// registered name. Aliases and the fallback action are
+    /// not included.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{ActionRegistry, Bindings, DeltaError};
+    /// let save = || "save";
+    /// let quit = || "quit";
+    /// let help = || "help";
+    /// let mut registry = ActionRegistry::new();
+    /// registry.register("save", &save);
+    /// registry.register("quit", &quit);
+    /// registry.register("help", &help);
+    /// let mut laptop: Bindings<String, &str> = Bindings::new();
+    /// laptop.bind_action("C-s", &save);
+    /// laptop.bind_action("C-q", &quit);
+    /// let mut desktop: Bindings<String, &str> = Bindings::new();
+    /// desktop.bind_action("C-s", &save);
+    /// desktop.bind_action("C-q", &quit);
+    ///
+    /// let base = laptop.subset(&["C-s", "C-q"]);
+    /// laptop.bind_action("F1", &help);
+    /// laptop.bind_action("C-q", &save);
+    /// let delta = laptop.delta_since(&base, &registry).unwrap();
+    /// assert_eq!(delta.added, vec![("F1".to_string(), "help".to_string())]);
+    /// assert_eq!(delta.changed.len(), 1);
+    /// desktop.apply_delta(&delta, &registry).unwrap();
+    /// assert_eq!(desktop.run_action("F1"), Some("help"));
+    /// assert_eq!(desktop.run_action("C-q"), Some("save"));
+    ///
+    /// // Applying again changes nothing.
+    /// desktop.apply_delta(&delta, &registry).unwrap();
+    ///
+    /// // A diverged binding is reported, and nothing is applied.
+    /// let mut other: Bindings<String, &str> = Bindings::new();
+    /// other.bind_action("C-q", &help);
+    /// let errors = other.apply_delta(&delta, &registry).unwrap_err();
+    /// assert_eq!(errors, vec![DeltaError::Conflict {
+    ///     event: "C-q".to_string(),
+    ///     found: Some("help".to_string()),
+    /// }]);
+    /// assert!(other.run_action("F1").is_none());
+    /// ```
+    pub fn delta_since(&self, base: &Bindings<'a, E, R>, registry: &ActionRegistry<'a, R>)
+                       -> Result<BindingsDelta<E>, Vec<DeltaError<E>>>
+    {
+        let mut delta = BindingsDelta::default();
+        let mut errors = Vec::new();
+        for (event, &action) in &self.store {
+            let new = match registry.name_of(action) {
+                Some(name) => name,
+                None => {
+                    errors.push(DeltaError::Unnamed(event.clone()));
+                    continue;
+                },
+            };
+            let old = match base.store.get::<E>(event) {
+                Some(&old) => old,
+                None => {
+                    delta.added.push((event.clone(), new.to_owned()));
+                    continue;
+                },
+            };
+            match registry.name_of(old) {
+                Some(old) if old == new => (),
+                Some(old) => delta.changed.push((event.clone(), old.to_owned(), new.to_owned())),
+                None => errors.push(DeltaError::Unnamed(event.clone())),
+            }
+        }
+        for (event, &action) in &base.store {
+            if self.store.contains_key::<E>(event) {
+                continue;
+            }
+            match registry.name_of(action) {
+                Some(old) => delta.removed.push((event.clone(), old.to_owned())),
+                None => errors.push(DeltaError::Unnamed(event.clone())),
+            }
+        }
+        if errors.is_empty() {
+            Ok(delta)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Apply `delta`, naming actions by `registry`, if it
+    /// can all be applied; otherwise change nothing and
+    /// return every problem. An entry applies if the
+    /// binding is as the delta changes it from, and is
+    /// skipped if the binding is already as the delta
+    /// changes it to; any other binding is a conflict.
+    pub fn apply_delta(&mut self, delta: &BindingsDelta<E>, registry: &ActionRegistry<'a, R>)
+                       -> Result<(), Vec<DeltaError<E>>>
+    {
+        let mut errors = Vec::new();
+        let mut binds = Vec::new();
+        let mut unbinds = Vec::new();
+        let entries = delta.added.iter().map(|(e, new)| (e, None, Some(new)))
+            .chain(delta.removed.iter().map(|(e, old)| (e, Some(old), None)))
+            .chain(delta.changed.iter().map(|(e, old, new)| (e, Some(old), Some(new))));
+        for (event, old, new) in entries {
+            let found = self.action_name(event, registry);
+            let bound = self.store.contains_key::<E>(event);
+            let is = |name: Option<&String>| match name {
+                Some(name) => found == Some(name.as_str()),
+                None => !bound,
+            };
+            if is(new) {
+                continue;
+            }
+            if !is(old) {
+                errors.push(DeltaError::Conflict {
+                    event: event.clone(),
+                    found: found.map(str::to_owned),
+                });
+                continue;
+            }
+            match new {
+                Some(name) => match registry.get(name) {
+                    Some(action) => binds.push((event.clone(), action)),
+                    None => errors.push(DeltaError::UnknownAction(name.clone())),
+                },
+                None => unbinds.push(event),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        for event in unbinds {
+            self.store.remove::<E>(event);
+        }
+        for (event, action) in binds {
+            self.insert(event, action);
+        }
+        Ok(())
+    }
+}
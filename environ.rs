@@ -0,0 +1,103 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Bindings read from environment variables, for
+//! configuring headless servers: `KBEHDZ_X=yell` binds
+//! `X` to the action registered as `yell`.
+
+use std::env;
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt;
+
+use {ActionRegistry, Bindings};
+
+/// Error from `Bindings::from_env()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    /// The variable names an action that is not
+    /// registered.
+    UnknownAction {
+        /// The name of the variable.
+        var: String,
+        /// The action name it holds.
+        name: String,
+    },
+    /// The variable has the prefix, but its name or value
+    /// is not Unicode.
+    NotUnicode(OsString),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::UnknownAction { ref var, ref name } =>
+                write!(f, "{}: unknown action {:?}", var, name),
+            LoadError::NotUnicode(ref var) =>
+                write!(f, "{}: not Unicode", var.to_string_lossy()),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+impl<'a, R> Bindings<'a, String, R>
+    where R: 'a
+{
+    /// Make bindings from the environment variables whose
+    /// names start with `prefix`, binding the rest of each
+    /// name to the action registered in `registry` under the
+    /// variable's value. A variable named just `prefix` is
+    /// ignored.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::env;
+    /// use kbehdz::{Action, ActionRegistry, Bindings};
+    /// let yell: Action<&str> = &|| "yell";
+    /// let mut registry = ActionRegistry::new();
+    /// registry.register("yell", yell);
+    /// env::set_var("KBEHDZ_DOCTEST_X", "yell");
+    /// let kc = Bindings::from_env("KBEHDZ_DOCTEST_", &registry).unwrap();
+    /// assert_eq!(kc.run_action("X"), Some("yell"));
+    /// assert_eq!(kc.len(), 1);
+    /// env::set_var("KBEHDZ_DOCTEST_Y", "scream");
+    /// match Bindings::from_env("KBEHDZ_DOCTEST_", &registry) {
+    ///     Err(err) => assert_eq!(err.to_string(),
+    ///                            "KBEHDZ_DOCTEST_Y: unknown action \"scream\""),
+    ///     Ok(_) => panic!("unknown action accepted"),
+    /// }
+    /// ```
+    pub fn from_env(prefix: &str, registry: &ActionRegistry<'a, R>)
+                    -> Result<Bindings<'a, String, R>, LoadError>
+    {
+        let mut bindings = Bindings::new();
+        for (var, value) in env::vars_os() {
+            let event = match var.to_str() {
+                Some(name) => match name.strip_prefix(prefix) {
+                    Some(event) => event,
+                    None => continue,
+                },
+                None => {
+                    if var.to_string_lossy().starts_with(prefix) {
+                        return Err(LoadError::NotUnicode(var));
+                    }
+                    continue;
+                },
+            };
+            if event.is_empty() {
+                continue;
+            }
+            let name = value.to_str().ok_or_else(|| LoadError::NotUnicode(var.clone()))?;
+            let action = registry.get(name).ok_or_else(|| LoadError::UnknownAction {
+                var: var.to_string_lossy().into_owned(),
+                name: name.to_owned(),
+            })?;
+            bindings.bind_action(event, action);
+        }
+        Ok(bindings)
+    }
+}
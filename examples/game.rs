@@ -0,0 +1,47 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+
+//! The `agitate` demo rewritten around a game state
+//! object, with keys bound to its methods.
+
+extern crate kbehdz;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use kbehdz::*;
+
+/// Sample game state.
+struct Game {
+    noise: u32,
+}
+
+impl Game {
+    /// A sample action.
+    fn yell(&mut self) -> String {
+        self.noise += 1;
+        "yell".to_string()
+    }
+
+    /// Another sample action.
+    fn scream(&mut self) -> String {
+        self.noise += 2;
+        "scream".to_string()
+    }
+}
+
+/// Mess around with the keybindings.
+fn main() {
+    let game = Rc::new(RefCell::new(Game { noise: 0 }));
+    let mut kbs: OwnedBindings<String, Result<String, MethodError>> =
+        OwnedBindings::new();
+    kbs.bind_method("X", Rc::clone(&game), Game::yell);
+    kbs.bind_method("Y", Rc::clone(&game), Game::scream);
+    println!("{}", kbs.run_action("X").unwrap().unwrap());
+    kbs.bind_method("X", Rc::clone(&game), Game::scream);
+    println!("{}", kbs.run_action("X").unwrap().unwrap());
+    println!("noise: {}", game.borrow().noise);
+}
@@ -0,0 +1,136 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Load a JSON keymap file against a demo action registry
+//! and print the resolved keymap, or every problem found
+//! in the file. Exits with status 1 if there are problems,
+//! so it can be used to lint keymap files.
+//!
+//! Run with
+//! `cargo run --features serde --example inspect -- examples/inspect.json`.
+//!
+//! The file is a versioned config, as saved by
+//! `ConfigSchema`, whose config is a list of bindings:
+//!
+//! ```text
+//! { "version": 1,
+//!   "config": [ { "key": "Ctrl+S", "action": "save" } ] }
+//! ```
+//!
+//! Keys are chords: modifiers `Ctrl`, `Alt`, `Shift` and
+//! `Super`, in any order and any case, joined by `+` to a
+//! final key, whose case matters only if it is more than
+//! one character. `Ctrl+C` is reserved.
+
+extern crate kbehdz;
+extern crate serde;
+
+use std::env;
+use std::process;
+
+use serde::Deserialize;
+
+use kbehdz::{ActionRegistry, Bindings, ConfigSchema};
+
+/// An entry of a keymap file.
+#[derive(Deserialize)]
+struct Entry {
+    key: String,
+    action: String,
+}
+
+/// Modifiers in canonical order.
+const MODIFIERS: &[&str] = &["Ctrl", "Alt", "Shift", "Super"];
+
+/// Parse a chord into canonical form, with modifiers in
+/// the order of `MODIFIERS` and single-letter keys in
+/// upper case.
+fn parse_chord(raw: &str) -> Result<String, String> {
+    let mut parts: Vec<&str> = raw.split('+').collect();
+    let key = parts.pop().unwrap();
+    if key.is_empty() {
+        return Err(format!("chord {:?} has no key", raw));
+    }
+    let mut held = [false; 4];
+    for part in parts {
+        let i = MODIFIERS
+            .iter()
+            .position(|m| m.eq_ignore_ascii_case(part))
+            .ok_or_else(|| format!("chord {:?} has unknown modifier {:?}", raw, part))?;
+        if held[i] {
+            return Err(format!("chord {:?} repeats modifier {}", raw, MODIFIERS[i]));
+        }
+        held[i] = true;
+    }
+    let mut chord: Vec<&str> = MODIFIERS
+        .iter()
+        .zip(&held)
+        .filter(|&(_, &held)| held)
+        .map(|(&m, _)| m)
+        .collect();
+    let key = if key.chars().count() == 1 {
+        key.to_uppercase()
+    } else {
+        key.to_string()
+    };
+    chord.push(&key);
+    Ok(chord.join("+"))
+}
+
+fn main() {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: inspect KEYMAP.json");
+            process::exit(2);
+        },
+    };
+
+    let demo: &[(&str, &dyn Fn() -> &'static str)] = &[
+        ("save", &|| "saved"),
+        ("open", &|| "opened"),
+        ("quit", &|| "quit"),
+        ("undo", &|| "undone"),
+        ("redo", &|| "redone"),
+        ("help", &|| "helped"),
+    ];
+    let mut registry = ActionRegistry::new();
+    for &(name, action) in demo {
+        registry.register(name, action);
+    }
+
+    let schema = ConfigSchema::new(1);
+    let entries: Vec<Entry> = match schema.load(&path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            process::exit(1);
+        },
+    };
+
+    let mut kc: Bindings<String, &str> = Bindings::new();
+    let mut load = kc.transaction();
+    load.reserve("Ctrl+C");
+    for entry in &entries {
+        load.try_bind_named(entry.key.as_str(), parse_chord, &entry.action, &registry);
+    }
+    if let Err(problems) = load.commit() {
+        for problem in &problems {
+            eprintln!("{}: key {:?}: {}", path, entries[problem.index].key, problem);
+        }
+        process::exit(1);
+    }
+
+    let mut keymap: Vec<(String, &str)> = kc
+        .to_vec()
+        .into_iter()
+        .map(|(key, action)| (key, registry.name_of(action).unwrap_or("?")))
+        .collect();
+    keymap.sort();
+    let width = keymap.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    for (key, name) in keymap {
+        println!("{:width$}  {}", key, name, width = width);
+    }
+}
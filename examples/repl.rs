@@ -0,0 +1,75 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! An interactive demo of the `kbehdz` crate: each line
+//! read from standard input is an event to dispatch, except
+//! for these commands:
+//!
+//! ```text
+//! bind EVENT NAME    bind EVENT to the command registered as NAME
+//! unbind EVENT       remove the binding for EVENT
+//! help               show the bindings and commands
+//! quit               exit
+//! ```
+//!
+//! `Bindings` borrows its actions, so they must outlive
+//! it. Here the actions live in an `ActionRegistry` made
+//! first, and new bindings are made at run time by name,
+//! through the binding script language.
+
+extern crate kbehdz;
+
+use std::cell::Cell;
+use std::io::{self, BufRead, Write};
+
+use kbehdz::{ActionRegistry, Bindings};
+
+fn main() {
+    let count = Cell::new(0);
+    let hello = || "hello, world".to_string();
+    let shout = || "HEY!".to_string();
+    let tick = || {
+        count.set(count.get() + 1);
+        format!("count is {}", count.get())
+    };
+    let mut registry = ActionRegistry::new();
+    registry.register("hello", &hello);
+    registry.register("shout", &shout);
+    registry.register("tick", &tick);
+
+    let mut kc: Bindings<String, String> = Bindings::new();
+    kc.apply_script("bind hi hello\nbind t tick", &registry).unwrap();
+
+    println!("type an event, or \"help\"");
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            break;
+        }
+        let line = line.trim();
+        let command = line.split_whitespace().next().unwrap_or("");
+        match command {
+            "" => (),
+            "quit" => break,
+            "help" => {
+                print!("{}", kc.dump_script(&registry));
+                println!("## commands: {}", registry.names().join(" "));
+                println!("## also: bind EVENT NAME, unbind EVENT, help, quit");
+            },
+            "bind" | "unbind" => {
+                if let Err(e) = kc.apply_script(line, &registry) {
+                    println!("{}", e);
+                }
+            },
+            _ => match kc.run_action(line) {
+                Some(result) => println!("{}", result),
+                None => println!("{}: unbound", line),
+            },
+        }
+    }
+}
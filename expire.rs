@@ -0,0 +1,168 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Bindings that expire, for events that are only
+//! meaningful for a while: "press F within 2 seconds".
+//!
+//! Times are always supplied by the caller, so that tests
+//! can drive the clock. A binding whose deadline has been
+//! reached behaves as unbound in every query, whether or
+//! not it has been purged yet.
+
+use std::borrow::{Borrow, ToOwned};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::Instant;
+
+use {Action, Bindings};
+
+/// An `ExpiringBindings` object is a `Bindings` in which
+/// some bindings have deadlines.
+///
+/// # Examples:
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use kbehdz::ExpiringBindings;
+/// let dodge = || "dodge";
+/// let pause = || "pause";
+/// let start = Instant::now();
+/// let mut kc = ExpiringBindings::new();
+/// kc.bind_action(&'p', &pause);
+/// kc.bind_until(&'f', &dodge, start + Duration::from_secs(2));
+/// let soon = start + Duration::from_secs(1);
+/// let late = start + Duration::from_secs(3);
+/// assert_eq!(kc.run_action_at(&'f', soon), Some("dodge"));
+/// assert!(kc.run_action_at(&'f', late).is_none());
+/// assert_eq!(kc.run_action_at(&'p', late), Some("pause"));
+/// assert_eq!(kc.len_at(late), 1);
+/// assert!(kc.snapshot_at(late).get_action(&'f').is_none());
+/// assert_eq!(kc.purge_expired(late), 1);
+/// assert!(kc.get_action_at(&'f', start).is_none());
+/// ```
+pub struct ExpiringBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    bindings: Bindings<'a, E, R>,
+    deadlines: HashMap<E, Instant>,
+}
+
+impl<'a, E, R> Default for ExpiringBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    fn default() -> Self {
+        ExpiringBindings::new()
+    }
+}
+
+impl<'a, E, R> ExpiringBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Make a new empty binding.
+    pub fn new() -> Self {
+        ExpiringBindings {
+            bindings: Bindings::new(),
+            deadlines: HashMap::new(),
+        }
+    }
+
+    /// Overwrite or create a binding that never expires.
+    pub fn bind_action<T>(&mut self, event: &T, action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + Hash + Eq + ?Sized
+    {
+        self.deadlines.remove(event);
+        self.bindings.bind_action(event, action);
+    }
+
+    /// Overwrite or create a binding that expires at
+    /// `deadline`.
+    pub fn bind_until<T>(&mut self, event: &T, action: Action<'a, R>, deadline: Instant)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.deadlines.insert(event.to_owned(), deadline);
+        self.bindings.bind_action(event, action);
+    }
+
+    /// Remove the binding for `event`, returning its action
+    /// whether or not it had expired.
+    pub fn unbind_action<T>(&mut self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.deadlines.remove(event);
+        self.bindings.unbind_action(event)
+    }
+
+    /// True if `event` has a deadline that has been reached
+    /// at `now`.
+    fn expired<T>(&self, event: &T, now: Instant) -> bool
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.deadlines.get(event).is_some_and(|&deadline| deadline <= now)
+    }
+
+    /// Given an event that is bound and unexpired at `now`,
+    /// run the corresponding action and return the result.
+    /// Otherwise return `None`.
+    pub fn run_action_at<T>(&self, event: &T, now: Instant) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.get_action_at(event, now).map(|action| action())
+    }
+
+    /// Given an event that is bound and unexpired at `now`,
+    /// return the corresponding action unexecuted.
+    /// Otherwise return `None`.
+    pub fn get_action_at<T>(&self, event: &T, now: Instant) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        if self.expired(event, now) {
+            return None;
+        }
+        self.bindings.get_action(event)
+    }
+
+    /// Return the deadline of `event`, if it has one.
+    pub fn deadline<T>(&self, event: &T) -> Option<Instant>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.deadlines.get(event).copied()
+    }
+
+    /// Number of bindings unexpired at `now`.
+    pub fn len_at(&self, now: Instant) -> usize {
+        self.bindings.store.keys().filter(|&event| !self.expired(event, now)).count()
+    }
+
+    /// Return a `Bindings` holding the bindings unexpired
+    /// at `now`, for the queries `Bindings` offers.
+    pub fn snapshot_at(&self, now: Instant) -> Bindings<'a, E, R>
+        where E: Clone
+    {
+        let mut snapshot = Bindings::new();
+        for (event, &action) in &self.bindings.store {
+            if !self.expired(event, now) {
+                snapshot.store.insert(event.clone(), action);
+            }
+        }
+        snapshot
+    }
+
+    /// Remove the bindings expired at `now`, returning how
+    /// many were removed.
+    pub fn purge_expired(&mut self, now: Instant) -> usize {
+        let deadlines = &mut self.deadlines;
+        let before = self.bindings.store.len();
+        self.bindings.store.retain(|event, _| {
+            match deadlines.get(event) {
+                Some(&deadline) if deadline <= now => {
+                    deadlines.remove(event);
+                    false
+                },
+                _ => true,
+            }
+        });
+        before - self.bindings.store.len()
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Fingerprints of binding sets that are the same on every
+//! machine and every run, for telling whether a user's
+//! bindings differ from a synced or cached copy.
+
+use std::fmt::Display;
+use std::hash::Hash;
+
+use {ActionRegistry, Bindings};
+
+/// FNV-1a offset basis and prime, 64-bit.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Continue the FNV-1a hash `hash` over `bytes`.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &b| (hash ^ u64::from(b)).wrapping_mul(FNV_PRIME))
+}
+
+/// Append `field` to `encoding`, preceded by its length as
+/// 8 little-endian bytes, or by 8 `0xff` bytes if absent.
+fn encode_field(encoding: &mut Vec<u8>, field: Option<&str>) {
+    match field {
+        Some(field) => {
+            encoding.extend_from_slice(&(field.len() as u64).to_le_bytes());
+            encoding.extend_from_slice(field.as_bytes());
+        },
+        None => encoding.extend_from_slice(&[0xff; 8]),
+    }
+}
+
+impl<'a, E, R> Bindings<'a, E, R>
+    where E: Hash + Eq + Display, R: 'a
+{
+    /// Return a fingerprint of the bindings, naming actions
+    /// by `registry`, that does not depend on the order of
+    /// binding, the machine or the run.
+    ///
+    /// The canonical encoding is: for each binding, the
+    /// event as formatted by `Display` and then the name of
+    /// its action by `ActionRegistry::name_of()`, each
+    /// preceded by its length in bytes as 8 little-endian
+    /// bytes, with an action that has no name encoded as 8
+    /// `0xff` bytes instead. Bindings are encoded in
+    /// order of their event text and then action name, and
+    /// the fingerprint is the 64-bit FNV-1a hash of the
+    /// whole encoding. Aliases and the fallback action are
+    /// not included.
+    ///
+    /// Events that format the same cannot be told apart,
+    /// nor can actions without names.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{ActionRegistry, Bindings};
+    /// let save = || "save";
+    /// let quit = || "quit";
+    /// let mut registry = ActionRegistry::new();
+    /// registry.register("save", &save);
+    /// registry.register("quit", &quit);
+    /// let mut one: Bindings<String, &str> = Bindings::new();
+    /// one.bind_action("C-s", &save);
+    /// one.bind_action("C-q", &quit);
+    /// let mut two: Bindings<String, &str> = Bindings::new();
+    /// two.bind_action("C-q", &quit);
+    /// two.bind_action("C-s", &save);
+    /// assert_eq!(one.content_hash(&registry), two.content_hash(&registry));
+    /// assert_eq!(one.content_hash(&registry), 0x3399_eb04_a5ea_61cf);
+    /// two.bind_action("C-s", &quit);
+    /// assert_ne!(one.content_hash(&registry), two.content_hash(&registry));
+    /// ```
+    pub fn content_hash(&self, registry: &ActionRegistry<'a, R>) -> u64 {
+        let mut pairs: Vec<(String, Option<&str>)> = self.store
+            .iter()
+            .map(|(event, &action)| (event.to_string(), registry.name_of(action)))
+            .collect();
+        pairs.sort();
+        let mut encoding = Vec::new();
+        for (event, name) in &pairs {
+            encode_field(&mut encoding, Some(event));
+            encode_field(&mut encoding, *name);
+        }
+        fnv1a(FNV_OFFSET, &encoding)
+    }
+}
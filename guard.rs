@@ -0,0 +1,134 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Bindings with a condition checked before the action and
+//! an effect run after it, kept with the binding rather
+//! than in separate maps.
+
+use std::borrow::{Borrow, ToOwned};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use {Action, Bindings};
+
+/// Type of conditions that must hold for an action to run.
+pub type Guard<'a> = &'a (dyn Fn() -> bool + 'a);
+
+/// Type of effects run with the result of an action.
+pub type AfterHook<'a, R> = &'a (dyn Fn(&R) + 'a);
+
+/// A `GuardedBindings` object is a `Bindings` in which each
+/// binding may have a guard and an after-hook. Running a
+/// binding checks its guard, runs its action, and then runs
+/// its after-hook with the result. If the guard fails
+/// nothing else runs.
+///
+/// # Examples:
+///
+/// ```
+/// use std::cell::Cell;
+/// use kbehdz::GuardedBindings;
+/// let dirty = Cell::new(false);
+/// let saves = Cell::new(0);
+/// let is_dirty = || dirty.get();
+/// let save = || "saved";
+/// let count = |_: &&str| saves.set(saves.get() + 1);
+/// let mut kc = GuardedBindings::new();
+/// kc.bind_full(&'s', Some(&is_dirty), &save, Some(&count));
+/// assert!(kc.run_action(&'s').is_none());
+/// assert_eq!(saves.get(), 0);
+/// dirty.set(true);
+/// assert_eq!(kc.run_action(&'s'), Some("saved"));
+/// assert_eq!(saves.get(), 1);
+/// kc.bind_action(&'s', &save);
+/// dirty.set(false);
+/// assert_eq!(kc.run_action(&'s'), Some("saved"));
+/// assert_eq!(saves.get(), 1);
+/// ```
+pub struct GuardedBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    bindings: Bindings<'a, E, R>,
+    hooks: HashMap<E, (Option<Guard<'a>>, Option<AfterHook<'a, R>>)>,
+}
+
+impl<'a, E, R> Default for GuardedBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    fn default() -> Self {
+        GuardedBindings::new()
+    }
+}
+
+impl<'a, E, R> GuardedBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Make a new empty binding.
+    pub fn new() -> Self {
+        GuardedBindings {
+            bindings: Bindings::new(),
+            hooks: HashMap::new(),
+        }
+    }
+
+    /// The bindings, without guards or after-hooks.
+    pub fn bindings(&self) -> &Bindings<'a, E, R> {
+        &self.bindings
+    }
+
+    /// Overwrite or create a binding with no guard or
+    /// after-hook.
+    pub fn bind_action<T>(&mut self, event: &T, action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + Hash + Eq + ?Sized
+    {
+        self.hooks.remove(event);
+        self.bindings.bind_action(event, action);
+    }
+
+    /// Overwrite or create a binding with an optional
+    /// `guard` and `after` hook.
+    pub fn bind_full<T>(&mut self,
+                        event: &T,
+                        guard: Option<Guard<'a>>,
+                        action: Action<'a, R>,
+                        after: Option<AfterHook<'a, R>>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + Hash + Eq + ?Sized
+    {
+        self.bind_action(event, action);
+        if guard.is_some() || after.is_some() {
+            self.hooks.insert(event.to_owned(), (guard, after));
+        }
+    }
+
+    /// Remove the binding for an event, with its guard and
+    /// after-hook, returning its action.
+    pub fn unbind_action<T>(&mut self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.hooks.remove(event);
+        self.bindings.unbind_action(event)
+    }
+
+    /// Given an event that is in the bindings and whose
+    /// guard passes, run the corresponding action and then
+    /// its after-hook, and return the result.  Return
+    /// `None` if no such event is bound or its guard fails.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        let action = self.bindings.get_action(event)?;
+        let (guard, after) = self.hooks.get(event).cloned().unwrap_or((None, None));
+        if let Some(guard) = guard {
+            if !guard() {
+                return None;
+            }
+        }
+        let result = action();
+        if let Some(after) = after {
+            after(&result);
+        }
+        Some(result)
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! A bounded log of recent commands, for showing what was
+//! done in a debug console. Unlike a session recording,
+//! the log keeps action names and times, and is not meant
+//! for replay.
+
+use std::collections::VecDeque;
+use std::collections::vec_deque;
+use std::hash::Hash;
+use std::iter::Rev;
+use std::time::{Duration, Instant};
+#[cfg(feature = "serde")]
+use std::io::{self, Write};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use {ActionRegistry, Bindings};
+
+/// A command in a `HistoryLog`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HistoryEntry<E> {
+    /// Time since the start of the log.
+    pub offset: Duration,
+    /// The event dispatched.
+    pub event: E,
+    /// Registered name of the action run, if it has one.
+    pub action_name: Option<String>,
+}
+
+/// A `HistoryLog` keeps the most recent successfully
+/// dispatched commands, up to its capacity.
+///
+/// # Examples:
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use kbehdz::{Action, ActionRegistry, Bindings, HistoryLog};
+/// let save: Action<()> = &|| ();
+/// let mut registry = ActionRegistry::new();
+/// registry.register("save", save);
+/// let mut kc = Bindings::new();
+/// kc.bind_action(&'s', save);
+/// let start = Instant::now();
+/// let mut log = HistoryLog::starting_at(2, start);
+/// for (i, event) in "sxss".chars().enumerate() {
+///     let when = start + Duration::from_secs(i as u64);
+///     log.dispatch_at(&kc, &registry, event, when);
+/// }
+/// let offsets: Vec<u64> = log.iter().map(|e| e.offset.as_secs()).collect();
+/// assert_eq!(offsets, vec![3, 2]);
+/// assert_eq!(log.iter().next().unwrap().action_name.as_deref(), Some("save"));
+/// log.clear();
+/// assert!(log.is_empty());
+///
+/// let unlimited: HistoryLog<char> = HistoryLog::new(usize::MAX);
+/// assert_eq!(unlimited.capacity(), usize::MAX);
+/// ```
+pub struct HistoryLog<E> {
+    start: Instant,
+    capacity: usize,
+    entries: VecDeque<HistoryEntry<E>>,
+}
+
+impl<E> HistoryLog<E> {
+    /// Make an empty log of at most `capacity` entries,
+    /// starting now.
+    pub fn new(capacity: usize) -> Self {
+        HistoryLog::starting_at(capacity, Instant::now())
+    }
+
+    /// Make an empty log of at most `capacity` entries,
+    /// starting at `start`. Nothing is allocated up front,
+    /// so `capacity` may be as large as `usize::MAX`.
+    pub fn starting_at(capacity: usize, start: Instant) -> Self {
+        HistoryLog {
+            start,
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Most entries kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// True if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add an entry for a command run at `when`, dropping
+    /// the oldest entry if the log is full. Times before
+    /// the start of the log are recorded as the start.
+    pub fn push(&mut self, when: Instant, event: E, action_name: Option<String>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        let offset = when.saturating_duration_since(self.start);
+        self.entries.push_back(HistoryEntry { offset, event, action_name });
+    }
+
+    /// Iterate over the entries, newest first.
+    pub fn iter(&self) -> Rev<vec_deque::Iter<'_, HistoryEntry<E>>> {
+        self.entries.iter().rev()
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Dispatch `event` to `bindings` as having happened at
+    /// `when` and return the result. If an action ran, log
+    /// the event with the action's name in `registry`.
+    pub fn dispatch_at<'a, R>(&mut self,
+                              bindings: &Bindings<'a, E, R>,
+                              registry: &ActionRegistry<'a, R>,
+                              event: E,
+                              when: Instant)
+                              -> Option<R>
+        where E: Hash + Eq
+    {
+        let action = bindings.get_action(&event).or_else(|| bindings.fallback())?;
+        let result = action();
+        let action_name = registry.name_of(action).map(str::to_owned);
+        self.push(when, event, action_name);
+        Some(result)
+    }
+
+    /// As `dispatch_at()`, at the current time.
+    pub fn dispatch<'a, R>(&mut self,
+                           bindings: &Bindings<'a, E, R>,
+                           registry: &ActionRegistry<'a, R>,
+                           event: E)
+                           -> Option<R>
+        where E: Hash + Eq
+    {
+        self.dispatch_at(bindings, registry, event, Instant::now())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<E: Serialize> HistoryLog<E> {
+    /// Write the entries as JSON lines, newest first.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for entry in self.iter() {
+            serde_json::to_writer(&mut writer, entry)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()
+    }
+}
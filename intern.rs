@@ -0,0 +1,155 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! String events stored as small integer symbols, for
+//! programs with many generated event names.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use {Action, Bindings};
+
+/// An interned string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+/// An `Interner` hands out a `Symbol` for each distinct
+/// string it is given. Each string is stored once, however
+/// many bindings use it.
+#[derive(Default)]
+pub struct Interner {
+    symbols: RefCell<HashMap<Rc<str>, Symbol>>,
+    names: RefCell<Vec<Rc<str>>>,
+}
+
+impl Interner {
+    /// Make a new empty interner.
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    /// Return the symbol for `name`, interning it if it is
+    /// new.
+    pub fn intern(&self, name: &str) -> Symbol {
+        if let Some(symbol) = self.lookup(name) {
+            return symbol;
+        }
+        let mut names = self.names.borrow_mut();
+        let symbol = Symbol(names.len() as u32);
+        let name: Rc<str> = Rc::from(name);
+        names.push(Rc::clone(&name));
+        self.symbols.borrow_mut().insert(name, symbol);
+        symbol
+    }
+
+    /// Return the symbol for `name`, or `None` if it has
+    /// never been interned.
+    pub fn lookup(&self, name: &str) -> Option<Symbol> {
+        self.symbols.borrow().get(name).copied()
+    }
+
+    /// Return the string `symbol` was made from.
+    pub fn resolve(&self, symbol: Symbol) -> Option<Rc<str>> {
+        self.names.borrow().get(symbol.0 as usize).cloned()
+    }
+
+    /// Number of strings interned.
+    pub fn len(&self) -> usize {
+        self.names.borrow().len()
+    }
+
+    /// True if no strings have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// An `InternedBindings` object binds string events to
+/// actions, storing each event as a `Symbol` from an
+/// `Interner` that may be shared with other bindings. Looking
+/// up a string costs one hash in the interner; looking up a
+/// symbol interned ahead of time costs none.
+///
+/// # Examples:
+///
+/// ```
+/// use std::rc::Rc;
+/// use kbehdz::{InternedBindings, Interner};
+/// let one = || 1;
+/// let two = || 2;
+/// let interner = Rc::new(Interner::new());
+/// let hot = interner.intern("inventory.slot.17.use");
+/// let mut play = InternedBindings::new(Rc::clone(&interner));
+/// let mut menu = InternedBindings::new(Rc::clone(&interner));
+/// play.bind_action("inventory.slot.17.use", &one);
+/// menu.bind_action("inventory.slot.17.use", &two);
+/// assert_eq!(interner.len(), 1);
+/// assert_eq!(play.run_symbol(hot), Some(1));
+/// assert_eq!(menu.run_action("inventory.slot.17.use"), Some(2));
+/// assert!(menu.run_action("quit").is_none());
+/// assert_eq!(interner.len(), 1);
+/// ```
+pub struct InternedBindings<'a, R: 'a> {
+    interner: Rc<Interner>,
+    bindings: Bindings<'a, Symbol, R>,
+}
+
+impl<'a, R> InternedBindings<'a, R> {
+    /// Make a new empty binding using `interner`.
+    pub fn new(interner: Rc<Interner>) -> Self {
+        InternedBindings {
+            interner,
+            bindings: Bindings::new(),
+        }
+    }
+
+    /// The interner for the events.
+    pub fn interner(&self) -> &Rc<Interner> {
+        &self.interner
+    }
+
+    /// The bindings, keyed by symbol.
+    pub fn bindings(&self) -> &Bindings<'a, Symbol, R> {
+        &self.bindings
+    }
+
+    /// Overwrite or create a binding, interning the event.
+    pub fn bind_action(&mut self, event: &str, action: Action<'a, R>) {
+        let symbol = self.interner.intern(event);
+        self.bind_symbol(symbol, action);
+    }
+
+    /// Overwrite or create a binding for an interned event.
+    pub fn bind_symbol(&mut self, symbol: Symbol, action: Action<'a, R>) {
+        self.bindings.bind_action(&symbol, action);
+    }
+
+    /// Remove the binding for `event`, returning its action.
+    pub fn unbind_action(&mut self, event: &str) -> Option<Action<'a, R>> {
+        let symbol = self.interner.lookup(event)?;
+        self.bindings.unbind_action(&symbol)
+    }
+
+    /// Given an event that is in the bindings, run the
+    /// corresponding action and return the result. Return
+    /// `None` if no such event is bound.
+    pub fn run_action(&self, event: &str) -> Option<R> {
+        self.get_action(event).map(|action| action())
+    }
+
+    /// As `run_action()`, for an interned event.
+    pub fn run_symbol(&self, symbol: Symbol) -> Option<R> {
+        self.bindings.run_action(&symbol)
+    }
+
+    /// Given an event that is in the bindings, return the
+    /// corresponding action unexecuted. Return `None` if no
+    /// such event is bound.
+    pub fn get_action(&self, event: &str) -> Option<Action<'a, R>> {
+        let symbol = self.interner.lookup(event)?;
+        self.bindings.get_action(&symbol)
+    }
+}
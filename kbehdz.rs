@@ -7,24 +7,375 @@
 //! Implementation of the "Command Pattern"
 //! <http://gameprogrammingpatterns.com/command.html>.
 
-use std::collections::HashMap;
-use std::hash::Hash;
-use std::borrow::{Borrow, ToOwned};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::borrow::{Borrow, Cow, ToOwned};
 use std::iter::FromIterator;
+use std::ptr;
+use std::sync::mpsc::Sender;
+
+use store::Store;
+
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+#[cfg(feature = "regex")]
+extern crate regex;
+#[cfg(feature = "arc-swap")]
+extern crate arc_swap;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "proptest")]
+extern crate proptest;
+
+#[cfg(feature = "arc-swap")]
+mod atomic;
+mod cancel;
+mod compose;
+#[cfg(feature = "serde")]
+mod config;
+mod context;
+mod coverage;
+mod debug;
+mod dedup;
+mod delta;
+mod environ;
+mod expire;
+mod fingerprint;
+mod guard;
+mod history;
+mod intern;
+mod matcher;
+mod memo;
+mod method;
+mod normalize;
+mod observe;
+mod overlay;
+mod owned;
+mod pattern;
+mod persistent;
+mod priority;
+mod profile;
+mod rebind;
+#[cfg(feature = "regex")]
+mod regexp;
+mod registry;
+mod remap;
+mod scan;
+mod script;
+mod session;
+mod shared;
+mod stack;
+mod store;
+mod taphold;
+mod tasks;
+#[cfg(feature = "tracing")]
+mod trace;
+mod transaction;
+mod weak;
+pub mod combinators;
+pub mod testing;
+#[cfg(feature = "process")]
+pub mod process;
+
+#[cfg(feature = "arc-swap")]
+pub use atomic::AtomicBindings;
+pub use cancel::{CancelRegistry, CancelToken, CancellableAction};
+pub use compose::{Compose, ComposeResult};
+#[cfg(feature = "serde")]
+pub use config::{ConfigError, ConfigSchema, Migration};
+pub use context::{ContextBindings, ContextSet};
+pub use coverage::Coverage;
+pub use debug::DebugBindings;
+pub use dedup::{DedupBindings, Deduped};
+pub use delta::{BindingsDelta, DeltaError};
+pub use environ::LoadError;
+pub use expire::ExpiringBindings;
+pub use guard::{AfterHook, Guard, GuardedBindings};
+pub use history::{HistoryEntry, HistoryLog};
+pub use intern::{InternedBindings, Interner, Symbol};
+pub use matcher::{Matcher, MatchingBindings};
+pub use memo::MemoBindings;
+pub use method::MethodError;
+pub use normalize::{NormalizeKey, NormalizingBindings};
+pub use observe::{BindingChange, ListenerId, ObservedBindings};
+pub use overlay::Overlay;
+pub use owned::{OwnedBindings, ScopedBindings};
+pub use pattern::PatternBindings;
+pub use persistent::PersistentBindings;
+pub use priority::{PriorityBindings, PriorityEntry, SourceId};
+pub use profile::{ProfiledBindings, Timing};
+pub use rebind::{CaptureOutcome, RebindCapture};
+#[cfg(feature = "regex")]
+pub use regexp::{RegexBindings, RegexId};
+pub use registry::ActionRegistry;
+pub use remap::{RemappedBindings, Remapper};
+pub use scan::ScanTable;
+pub use script::{AppliedSummary, ScriptError, ScriptErrorKind};
+pub use session::{Record, Replayer, ReplayReport, SessionRecorder, SessionReplay, Speed};
+pub use shared::{SharedAction, SharedBindings};
+pub use stack::KeymapStack;
+pub use taphold::{Edge, Interrupt, TapHold};
+pub use tasks::{DispatchPolicy, Dispatched, TaskOutcome, TaskTracker};
+pub use transaction::{Problem, ProblemKind, Transaction};
+pub use weak::WeakBindings;
+
+/// Return the shared action that does nothing but return
+/// `R::default()`. Binding it disables an event without
+/// unbinding it, so that it still shadows the event in
+/// layers below.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{noop, Bindings};
+/// let fire = || 5;
+/// let mut base = Bindings::new();
+/// base.bind_action(&'f', &fire);
+/// let mut top = Bindings::new();
+/// top.bind_action(&'f', noop());
+/// let lookup = |event| top.get_action(event).or_else(|| base.get_action(event));
+/// assert_eq!(lookup(&'f').unwrap()(), 0);
+/// assert!(lookup(&'g').is_none());
+/// ```
+pub fn noop<'a, R: Default + 'a>() -> Action<'a, R> {
+    fn nothing<R: Default>() -> R {
+        R::default()
+    }
+    &nothing::<R>
+}
+
+/// Error from `Bindings::bind_alias()` when an alias would
+/// lead back to itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AliasLoop;
+
+impl Display for AliasLoop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "alias would loop")
+    }
+}
+
+impl Error for AliasLoop {}
+
+/// Hook called with unbound events.
+type UnboundHook<'a, E> = Box<dyn Fn(&E) + 'a>;
+
+/// Callback called with rebound events.
+type RebindHook<'a, E> = &'a (dyn Fn(&E) + 'a);
+
+/// Which action `Bindings::run_action_reporting()` ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// The action bound to the event.
+    Bound,
+    /// The fallback action, since the event is unbound.
+    Fallback,
+}
+
+/// What `Bindings::dispatch()` did with an event, as given
+/// to the dispatch sink. The event and result are borrowed,
+/// so a sink that ignores them costs no clones.
+#[derive(Clone, Copy)]
+pub struct DispatchRecord<'r, E: 'r, R: 'r> {
+    /// The dispatched event.
+    pub event: &'r E,
+    /// The action that ran, if any.
+    pub action: Option<Action<'r, R>>,
+    /// Whether the bound action or the fallback ran, if
+    /// either did.
+    pub source: Option<Source>,
+    /// The result of the action, if one ran.
+    pub result: Option<&'r R>,
+}
+
+/// Sink called with each dispatch.
+type DispatchSink<'a, E, R> = Box<dyn Fn(DispatchRecord<'_, E, R>) + 'a>;
 
 /// Type of actions with the given result type.
-pub type Action<'a, R> = &'a (Fn () -> R + 'a);
+pub type Action<'a, R> = &'a (dyn Fn() -> R + 'a);
+
+/// True if two actions are the same action. Actions are
+/// compared by reference (including their vtable, since
+/// distinct zero-sized closures may share an address)
+/// rather than by behavior.
+fn same_action<R>(a: Action<R>, b: Action<R>) -> bool {
+    ptr::eq(a, b)
+}
+
+/// Address of an action, for hashing. Equal actions by
+/// `same_action()` have equal addresses.
+fn action_address<R>(action: Action<R>) -> usize {
+    action as *const dyn Fn() -> R as *const () as usize
+}
+
+/// Return the `names` starting with `prefix`, sorted and
+/// without duplicates, optionally ignoring case.
+fn completions<I>(names: I, prefix: &str, ignore_case: bool) -> Vec<String>
+    where I: Iterator<Item=String>
+{
+    let lower_prefix = prefix.to_lowercase();
+    let mut matches: Vec<String> = names
+        .filter(|name| {
+            if ignore_case {
+                name.to_lowercase().starts_with(&lower_prefix)
+            } else {
+                name.starts_with(prefix)
+            }
+        })
+        .collect();
+    matches.sort();
+    matches.dedup();
+    matches
+}
+
+/// True if making `alias` a synonym for `target` in
+/// `aliases` would lead back to `alias`.
+fn alias_loops<E, T>(aliases: &HashMap<E, E>, alias: &T, target: &T) -> bool
+    where E: Borrow<T> + Hash + Eq, T: Hash + Eq + ?Sized
+{
+    let mut next = target;
+    for _ in 0..=aliases.len() {
+        if next == alias {
+            return true;
+        }
+        match aliases.get(next) {
+            Some(e) => next = e.borrow(),
+            None => break,
+        }
+    }
+    false
+}
+
+/// Make an action that runs `a` and then `b`, returning
+/// the result of `b`. This is `combinators::seq()` for
+/// two `Action`s.
+///
+/// # Examples:
+///
+/// ```
+/// use std::cell::Cell;
+/// use kbehdz::{chain, Bindings, OwnedBindings};
+/// let count = Cell::new(0);
+/// let inc = || {
+///     count.set(count.get() + 1);
+///     count.get()
+/// };
+/// let twice = chain(&inc, &inc);
+/// let mut kc = Bindings::new();
+/// kc.bind_action("+", &twice);
+/// assert_eq!(kc.run_action("+").unwrap(), 2);
+/// assert_eq!(count.get(), 2);
+///
+/// let mut owned = OwnedBindings::new();
+/// owned.bind_action("hi", Box::new(chain(&|| "hello", &|| "world")));
+/// assert_eq!(owned.run_action("hi").unwrap(), "world");
+/// ```
+pub fn chain<'a, R>(a: Action<'a, R>, b: Action<'a, R>) -> impl Fn() -> R + 'a
+    where R: 'a
+{
+    combinators::seq(a, b)
+}
 
-// A `HashMap` is a great way to represent bindings:
-// efficient lookup and interior mutability. Newtype this
-// to avoid confusion in larger programs and for
-// readability.
+/// Make an action of any result type from a function that
+/// never returns, such as one calling `process::exit()`, so
+/// that it can be bound alongside ordinary actions. The
+/// function is taken as a `fn() -> !` pointer, since
+/// closure traits returning `!` are not yet stable Rust.
+///
+/// # Examples:
+///
+/// ```
+/// use std::process;
+/// use kbehdz::{diverging, Bindings};
+/// fn quit() -> ! {
+///     process::exit(0)
+/// }
+/// let quit = diverging(quit);
+/// let save = || "saved";
+/// let mut kc = Bindings::new();
+/// kc.bind_action(&'q', &quit);
+/// kc.bind_action(&'s', &save);
+/// assert!(kc.get_action(&'q').is_some());
+/// assert_eq!(kc.run_action(&'s'), Some("saved"));
+/// ```
+pub fn diverging<R>(f: fn() -> !) -> impl Fn() -> R {
+    move || f()
+}
+
+/// Make `n` bindings for benchmarking: event `"event-i"`
+/// is bound to an action returning `i % 4`, for each `i`
+/// below `n`. The result is the same on every call.
+#[cfg(feature = "bench-helpers")]
+pub fn sample_bindings(n: usize) -> Bindings<'static, String, usize> {
+    fn zero() -> usize { 0 }
+    fn one() -> usize { 1 }
+    fn two() -> usize { 2 }
+    fn three() -> usize { 3 }
+    const ACTIONS: [Action<'static, usize>; 4] = [&zero, &one, &two, &three];
+    let mut kc = Bindings::new();
+    for i in 0..n {
+        kc.store.insert(format!("event-{}", i), ACTIONS[i % ACTIONS.len()]);
+    }
+    kc
+}
+
+// A map is a great way to represent bindings: efficient
+// lookup and interior mutability. The `Store` is a
+// `HashMap` that keeps small maps in a vector, since most
+// keymaps are small.
 
 /// A `Bindings` object manages bindings between events
 /// and actions. It has the capability to execute the
 /// selected action given an event.
-pub struct Bindings<'a, E, R>(HashMap<E, Action<'a, R>>)
-    where E: Hash + Eq, R: 'a;
+pub struct Bindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// The bound events and their actions.
+    store: Store<E, Action<'a, R>>,
+    /// Action run for unbound events, if any.
+    fallback: Option<Action<'a, R>>,
+    /// Hook called with unbound events, if any.
+    unbound_hook: Option<UnboundHook<'a, E>>,
+    /// Aliases and their targets.
+    aliases: HashMap<E, E>,
+    /// Sink told of every dispatch, if any.
+    sink: Option<DispatchSink<'a, E, R>>,
+    /// Callback called with rebound events, if any.
+    on_rebind: Option<RebindHook<'a, E>>,
+}
+
+/// Build a `Bindings` from `event => action` pairs, each
+/// as would be passed to `Bindings::bind_action()`.
+///
+/// # Examples:
+///
+/// ```
+/// #[macro_use] extern crate kbehdz;
+/// # fn main() {
+/// let fire = || "fire";
+/// let jump = || "jump";
+/// let kc = bindings! {
+///     &'f' => &fire,
+///     &'j' => &jump,
+/// };
+/// assert_eq!(kc.len(), 2);
+/// assert_eq!(kc.run_action(&'j'), Some("jump"));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! bindings {
+    ($($event:expr => $action:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut bindings = $crate::Bindings::new();
+        $(bindings.bind_action($event, $action);)*
+        bindings
+    }};
+}
 
 impl<'a, T, E, R> FromIterator<&'a (&'a T, Action<'a, R>)>
     for Bindings<'a, E, R>
@@ -39,7 +390,7 @@ impl<'a, T, E, R> FromIterator<&'a (&'a T, Action<'a, R>)>
     ///
     /// ```
     /// use kbehdz::{Action, Bindings};
-    /// fn build_action<'a>(n: usize) -> Box<Fn() -> usize> {
+    /// fn build_action<'a>(n: usize) -> Box<dyn Fn() -> usize> {
     ///     Box::new(move || { n })
     /// }
     /// let aok = build_action(1);
@@ -52,12 +403,132 @@ impl<'a, T, E, R> FromIterator<&'a (&'a T, Action<'a, R>)>
     fn from_iter<U>(iter: U) -> Self
         where U: IntoIterator<Item=&'a (&'a T, Action<'a, R>)>
     {
-        let mut kbs: Bindings<E, R> = Bindings::new();
-        for (key, action) in iter {
-            let e: E = (*key).to_owned();
-            kbs.0.insert(e, action);
-        }
-        kbs
+        Bindings::with_init_handler(iter, |_| ())
+    }
+}
+
+/// Owning iterator over the bindings of a `Bindings`, in
+/// no particular order.
+pub struct IntoIter<'a, E, R: 'a>(store::IntoIter<E, Action<'a, R>>);
+
+impl<'a, E, R> Iterator for IntoIter<'a, E, R> {
+    type Item = (E, Action<'a, R>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+impl<'a, E, R> IntoIterator for Bindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    type Item = (E, Action<'a, R>);
+    type IntoIter = IntoIter<'a, E, R>;
+
+    /// Consume the bindings, yielding each event with its
+    /// action. Aliases and the fallback action are dropped.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use kbehdz::{Action, Bindings};
+    /// let one = || 1;
+    /// let two = || 2;
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action(&'b', &two);
+    /// kc.bind_action(&'a', &one);
+    /// let sorted: BTreeMap<char, Action<i32>> = kc.into_iter().collect();
+    /// assert_eq!(sorted.len(), 2);
+    /// let results: Vec<(char, i32)> = sorted
+    ///     .into_iter()
+    ///     .map(|(event, action)| (event, action()))
+    ///     .collect();
+    /// assert_eq!(results, vec![('a', 1), ('b', 2)]);
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.store.into_iter())
+    }
+}
+
+impl<'a, E, R> Default for Bindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    fn default() -> Self {
+        Bindings::new()
+    }
+}
+
+/// Bindings are equal when they bind the same events to the
+/// same actions and have the same fallback. Actions are
+/// compared by identity, not behavior: two separately
+/// written closures that do the same thing are different
+/// actions.
+///
+/// # Examples:
+///
+/// ```
+/// use std::collections::HashSet;
+/// use kbehdz::Bindings;
+/// let one = || 1;
+/// let other_one = || 1;
+/// let mut kc1 = Bindings::new();
+/// let mut kc2 = Bindings::new();
+/// for event in &['a', 'b', 'c'] {
+///     kc1.bind_action(event, &one);
+/// }
+/// for event in &['c', 'b', 'a'] {
+///     kc2.bind_action(event, &one);
+/// }
+/// assert!(kc1 == kc2);
+/// let mut set = HashSet::new();
+/// set.insert(kc1);
+/// assert!(set.contains(&kc2));
+/// kc2.bind_action(&'a', &other_one);
+/// assert!(!set.contains(&kc2));
+/// ```
+impl<'a, E, R> PartialEq for Bindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    fn eq(&self, other: &Self) -> bool {
+        let same_fallback = match (self.fallback, other.fallback) {
+            (Some(a), Some(b)) => same_action(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        same_fallback
+            && self.aliases == other.aliases
+            && self.store.len() == other.store.len()
+            && self.store.iter().all(|(event, &action)| {
+                other.store.get(event).is_some_and(|&a| same_action(a, action))
+            })
+    }
+}
+
+impl<'a, E, R> Eq for Bindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{}
+
+/// Hashes by events and action identities, consistently
+/// with `PartialEq`. Bindings are unordered, so each binding
+/// is hashed separately and the results combined in an
+/// order-independent way.
+impl<'a, E, R> Hash for Bindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let combined = self.store
+            .iter()
+            .map(|(event, &action)| {
+                let mut hasher = DefaultHasher::new();
+                event.hash(&mut hasher);
+                action_address(action).hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(0_u64, u64::wrapping_add);
+        self.store.len().hash(state);
+        combined.hash(state);
+        self.fallback.map(action_address).hash(state);
     }
 }
 
@@ -66,12 +537,84 @@ impl <'a, E, R> Bindings<'a, E, R>
 {
     /// Make a new empty binding.
     pub fn new() -> Self {
-        Bindings(HashMap::new())
+        Bindings {
+            store: Store::new(),
+            fallback: None,
+            unbound_hook: None,
+            aliases: HashMap::new(),
+            sink: None,
+            on_rebind: None,
+        }
+    }
+
+    /// Make a new `Bindings` out of the iterator, as with
+    /// `collect()`, calling `on_conflict` with the event
+    /// each time an event is bound again. The last binding
+    /// for an event wins.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, Bindings};
+    /// let one: Action<usize> = &|| 1;
+    /// let two: Action<usize> = &|| 2;
+    /// let bindings = &[("a", one), ("b", one), ("a", two)];
+    /// let mut conflicts = Vec::new();
+    /// let kc: Bindings<String, usize> =
+    ///     Bindings::with_init_handler(bindings.iter(), |e: &String| {
+    ///         conflicts.push(e.clone())
+    ///     });
+    /// assert_eq!(conflicts, vec!["a".to_string()]);
+    /// assert_eq!(kc.run_action("a").unwrap(), 2);
+    /// ```
+    pub fn with_init_handler<T, U, F>(bindings: U, mut on_conflict: F) -> Self
+        where U: IntoIterator<Item=&'a (&'a T, Action<'a, R>)>,
+              E: Borrow<T>,
+              T: ToOwned<Owned=E> + ?Sized + 'a,
+              F: FnMut(&E)
+    {
+        let mut kbs: Bindings<E, R> = Bindings::new();
+        for (key, action) in bindings {
+            let event = (*key).to_owned();
+            if kbs.store.contains_key::<E>(&event) {
+                on_conflict(&event);
+            }
+            kbs.store.insert(event, action);
+        }
+        kbs
+    }
+
+    /// Make a new `Bindings` out of an iterator of owned
+    /// events and their actions, such as one built at run
+    /// time. The last binding for an event wins.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let yell = || "yell";
+    /// let scream = || "scream";
+    /// let kc = Bindings::from_iter_owned(vec![
+    ///     ("a".to_string(), &yell as &dyn Fn() -> &'static str),
+    ///     ("b".to_string(), &scream),
+    /// ].into_iter());
+    /// assert_eq!(kc.run_action("a"), Some("yell"));
+    /// assert_eq!(kc.run_action("b"), Some("scream"));
+    /// ```
+    pub fn from_iter_owned<I>(bindings: I) -> Self
+        where I: IntoIterator<Item=(E, Action<'a, R>)>
+    {
+        let mut kbs: Bindings<E, R> = Bindings::new();
+        for (event, action) in bindings {
+            kbs.store.insert(event, action);
+        }
+        kbs
     }
 
     /// Given an event that is in the bindings, run the
-    /// corresponding action and return the result.  Return
-    /// `None` if no such event is bound.
+    /// corresponding action and return the result. If no
+    /// such event is bound, run the fallback action instead,
+    /// or return `None` if there is none.
     ///
     /// # Examples:
     ///
@@ -88,9 +631,248 @@ impl <'a, E, R> Bindings<'a, E, R>
     pub fn run_action<T>(&self, event: &T) -> Option<R>
         where E: Borrow<T>, T: Hash + Eq + ?Sized
     {
-        self.get_action(event).and_then(|action| Some(action()))
+        self.run_action_reporting(event).map(|(result, _)| result)
+    }
+
+    /// Like `run_action()`, but pass the result through `f`
+    /// before returning it. The mapping is chosen per call,
+    /// so different callers can adapt the same bindings
+    /// differently; to change what an action itself returns,
+    /// bind an action made with `combinators::map()`
+    /// instead.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let count = || 3_usize;
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action("count", &count);
+    /// let shown = kc.run_action_mapped("count", |n| format!("{} items", n));
+    /// assert_eq!(shown, Some("3 items".to_string()));
+    /// assert_eq!(kc.run_action("count"), Some(3));
+    /// assert!(kc.run_action_mapped("size", |n| n + 1).is_none());
+    /// ```
+    pub fn run_action_mapped<T, S, F>(&self, event: &T, f: F) -> Option<S>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized, F: FnOnce(R) -> S
+    {
+        self.run_action(event).map(f)
+    }
+
+    /// As `run_action()`, but also report whether the bound
+    /// action or the fallback ran, so that unbound events
+    /// can be told apart from real commands.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Bindings, Source};
+    /// let fire = || "fire";
+    /// let beep = || "beep";
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action(&'f', &fire);
+    /// assert!(kc.run_action_reporting(&'x').is_none());
+    /// kc.set_fallback(&beep);
+    /// assert_eq!(kc.run_action_reporting(&'f'), Some(("fire", Source::Bound)));
+    /// assert_eq!(kc.run_action_reporting(&'x'), Some(("beep", Source::Fallback)));
+    /// ```
+    pub fn run_action_reporting<T>(&self, event: &T) -> Option<(R, Source)>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        match self.get_action(event) {
+            Some(action) => Some((action(), Source::Bound)),
+            None => self.fallback.map(|fallback| (fallback(), Source::Fallback)),
+        }
+    }
+
+    /// Set the action run by `run_action()` for unbound
+    /// events, returning the previous one if any.
+    pub fn set_fallback(&mut self, action: Action<'a, R>) -> Option<Action<'a, R>> {
+        self.fallback.replace(action)
+    }
+
+    /// Remove the fallback action, returning it if any.
+    pub fn clear_fallback(&mut self) -> Option<Action<'a, R>> {
+        self.fallback.take()
+    }
+
+    /// Return the fallback action, if any.
+    pub fn fallback(&self) -> Option<Action<'a, R>> {
+        self.fallback
+    }
+
+    /// Set a hook to be called by `dispatch()` with each
+    /// event that is neither bound nor handled by a fallback
+    /// action, for logging or telling the user. The hook
+    /// cannot change the bindings.
+    pub fn set_unbound_hook<F>(&mut self, hook: F)
+        where F: Fn(&E) + 'a
+    {
+        self.unbound_hook = Some(Box::new(hook));
+    }
+
+    /// Remove the unbound-event hook.
+    pub fn clear_unbound_hook(&mut self) {
+        self.unbound_hook = None;
+    }
+
+    /// Set a sink to be called by `dispatch()` after each
+    /// dispatch, bound or not, with a record of what ran.
+    /// This is for publishing input to other parts of a
+    /// program without coupling them to the bindings.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::sync::mpsc::channel;
+    /// use kbehdz::{Bindings, DispatchRecord, Source};
+    /// let map = || "map";
+    /// let (tx, rx) = channel();
+    /// let mut kc: Bindings<String, &str> = Bindings::new();
+    /// kc.bind_action("m", &map);
+    /// kc.set_dispatch_sink(move |record: DispatchRecord<String, &str>| {
+    ///     let sent = (record.event.clone(), record.source, record.result.cloned());
+    ///     tx.send(sent).unwrap();
+    /// });
+    /// kc.dispatch("m");
+    /// kc.dispatch("x");
+    /// assert_eq!(rx.try_recv(), Ok(("m".to_string(), Some(Source::Bound), Some("map"))));
+    /// assert_eq!(rx.try_recv(), Ok(("x".to_string(), None, None)));
+    /// ```
+    pub fn set_dispatch_sink<F>(&mut self, sink: F)
+        where F: Fn(DispatchRecord<'_, E, R>) + 'a
+    {
+        self.sink = Some(Box::new(sink));
+    }
+
+    /// Remove the dispatch sink.
+    pub fn clear_dispatch_sink(&mut self) {
+        self.sink = None;
+    }
+
+    /// As `run_action()`, but call the unbound-event hook if
+    /// nothing runs, and the dispatch sink in any case. The
+    /// hook is not called when the fallback action runs.
+    /// `run_action()` itself never calls the hook or sink,
+    /// since they need an owned event.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::cell::Cell;
+    /// use kbehdz::Bindings;
+    /// let fire = || "fire";
+    /// let unknown = Cell::new(0);
+    /// let mut kc: Bindings<String, &str> = Bindings::new();
+    /// kc.bind_action("f", &fire);
+    /// kc.set_unbound_hook(|_: &String| unknown.set(unknown.get() + 1));
+    /// assert_eq!(kc.dispatch("f"), Some("fire"));
+    /// assert!(kc.dispatch("x").is_none());
+    /// assert!(kc.run_action("x").is_none());
+    /// assert_eq!(unknown.get(), 1);
+    /// ```
+    pub fn dispatch<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: ToOwned<Owned=E> + Hash + Eq + ?Sized
+    {
+        let ran = match self.get_action(event) {
+            Some(action) => Some((action, Source::Bound)),
+            None => self.fallback.map(|fallback| (fallback, Source::Fallback)),
+        };
+        let result = ran.map(|(action, _)| action());
+        if result.is_none() {
+            if let Some(ref hook) = self.unbound_hook {
+                hook(&event.to_owned());
+            }
+        }
+        if let Some(ref sink) = self.sink {
+            // Borrow the stored event where there is one.
+            let owned;
+            let bound = self.store
+                .get_key_value(event)
+                .map(|(key, _)| key)
+                .or_else(|| self.aliases.get_key_value::<T>(event).map(|(key, _)| key));
+            let event = match bound {
+                Some(key) => key,
+                None => {
+                    owned = event.to_owned();
+                    &owned
+                },
+            };
+            sink(DispatchRecord {
+                event,
+                action: ran.map(|(action, _)| action),
+                source: ran.map(|(_, source)| source),
+                result: result.as_ref(),
+            });
+        }
+        result
+    }
+
+    /// Given an event that is in the bindings, run the
+    /// corresponding action and return the result. Panic
+    /// with `msg` and the event if no such event is bound:
+    /// this is for when a missing binding is a bug.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// use kbehdz::Bindings;
+    /// let one = || 1;
+    /// let mut kc: Bindings<String, usize> = Bindings::new();
+    /// kc.bind_action("a", &one);
+    /// assert_eq!(kc.run_expect("a", "missing a"), 1);
+    /// let err = catch_unwind(AssertUnwindSafe(|| {
+    ///     kc.run_expect("b", "missing command")
+    /// })).unwrap_err();
+    /// let msg = err.downcast_ref::<String>().unwrap();
+    /// assert!(msg.contains("missing command"));
+    /// assert!(msg.contains("\"b\""));
+    /// ```
+    #[must_use]
+    pub fn run_expect<T>(&self, event: &T, msg: &str) -> R
+        where E: Borrow<T>, T: Hash + Eq + Debug + ?Sized
+    {
+        match self.run_action(event) {
+            Some(result) => result,
+            None => panic!("{}: {:?}", msg, event),
+        }
+    }
+
+    /// Given an event that is in the bindings, run the
+    /// corresponding action and send the result on `tx`,
+    /// for handing commands to a consumer elsewhere. Return
+    /// false if no action ran. As with `run_action()`, the
+    /// fallback action runs for unbound events. A result
+    /// that cannot be sent because the receiver is gone is
+    /// dropped.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::sync::mpsc::channel;
+    /// use kbehdz::Bindings;
+    /// let quit = || "quit";
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action(&'q', &quit);
+    /// let (tx, rx) = channel();
+    /// assert!(kc.dispatch_to(&'q', &tx));
+    /// assert!(!kc.dispatch_to(&'x', &tx));
+    /// assert_eq!(rx.try_recv(), Ok("quit"));
+    /// assert!(rx.try_recv().is_err());
+    /// ```
+    pub fn dispatch_to<T>(&self, event: &T, tx: &Sender<R>) -> bool
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        match self.run_action(event) {
+            Some(result) => {
+                let _ = tx.send(result);
+                true
+            },
+            None => false,
+        }
     }
-    
+
     // XXX See
     // <http://github.com/rust-lang/rust/issues/31228#issuecomment-178293678>
     // for why the types are a little funny.
@@ -112,9 +894,200 @@ impl <'a, E, R> Bindings<'a, E, R>
     pub fn bind_action<T>(&mut self, event: &T, action: Action<'a, R>)
         where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
     {
-        self.0.insert(event.to_owned(), action);
+        self.insert(event.to_owned(), action);
+    }
+
+    /// Bind `event` to `action`, calling the rebind
+    /// callback if this replaces a binding.
+    fn insert(&mut self, event: E, action: Action<'a, R>) {
+        if let Some(on_rebind) = self.on_rebind {
+            if self.store.contains_key::<E>(&event) {
+                on_rebind(&event);
+            }
+        }
+        self.store.insert(event, action);
+    }
+
+    /// Set a callback to be called with the event whenever
+    /// a binding replaces an existing one, just before the
+    /// replacement, as for updating a settings screen. It
+    /// is not called for new bindings.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use kbehdz::Bindings;
+    /// let walk = || "walk";
+    /// let run = || "run";
+    /// let rebound = RefCell::new(Vec::new());
+    /// let note = |event: &char| rebound.borrow_mut().push(*event);
+    /// let mut kc = Bindings::new();
+    /// kc.set_on_rebind(&note);
+    /// kc.bind_action(&'w', &walk);
+    /// assert!(rebound.borrow().is_empty());
+    /// kc.bind_action(&'w', &run);
+    /// assert_eq!(*rebound.borrow(), vec!['w']);
+    /// assert_eq!(kc.run_action(&'w'), Some("run"));
+    /// ```
+    pub fn set_on_rebind(&mut self, callback: &'a dyn Fn(&E)) {
+        self.on_rebind = Some(callback);
+    }
+
+    /// Remove the rebind callback.
+    pub fn clear_on_rebind(&mut self) {
+        self.on_rebind = None;
+    }
+
+    /// Overwrite or create a binding to any reference to a
+    /// function or closure. This is `bind_action()` with the
+    /// coercion to `Action` done here, so that a mistake
+    /// such as passing the function itself is reported in
+    /// terms of the `Fn` bound rather than the trait object.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// fn yell() -> String {
+    ///     "yell".to_string()
+    /// }
+    /// let whisper = || "whisper".to_string();
+    /// let mut kc = Bindings::new();
+    /// kc.bind_fn(&'y', &yell);
+    /// kc.bind_fn(&'w', &whisper);
+    /// assert_eq!(kc.run_action(&'y').unwrap(), "yell");
+    /// assert_eq!(kc.run_action(&'w').unwrap(), "whisper");
+    /// ```
+    pub fn bind_fn<T, F>(&mut self, event: &T, f: &'a F)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized, F: Fn() -> R + 'a
+    {
+        self.bind_action(event, f);
+    }
+
+    /// Overwrite or create a binding for anything that
+    /// converts into an event.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let one = || 1;
+    /// let two = || 2;
+    /// let mut kc: Bindings<String, usize> = Bindings::new();
+    /// kc.bind_into("a", &one);
+    /// kc.bind_into('b', &two);
+    /// assert_eq!(kc.run_action("a"), Some(1));
+    /// assert_eq!(kc.run_action("b"), Some(2));
+    /// ```
+    pub fn bind_into<K>(&mut self, event: K, action: Action<'a, R>)
+        where K: Into<E>
+    {
+        self.insert(event.into(), action);
+    }
+
+    /// Bind `event` to `noop()`, so that it does nothing
+    /// but return `R::default()`. This is distinct from
+    /// unbinding it: the event is still bound.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let mut kc: Bindings<char, u32> = Bindings::new();
+    /// kc.bind_noop(&'q');
+    /// assert_eq!(kc.run_action(&'q'), Some(0));
+    /// ```
+    pub fn bind_noop<T>(&mut self, event: &T)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized, R: Default
+    {
+        self.bind_action(event, noop());
+    }
+
+    /// Overwrite or create a binding. An owned event is
+    /// used as is; only a borrowed one is converted to an
+    /// owned type.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    /// use kbehdz::Bindings;
+    /// let one = || 1;
+    /// let two = || 2;
+    /// let mut kc: Bindings<String, usize> = Bindings::new();
+    /// kc.bind_cow(Cow::Borrowed("a"), &one);
+    /// let owned: Cow<str> = Cow::Owned("b".to_string());
+    /// kc.bind_cow(owned, &two);
+    /// assert_eq!(kc.run_action("a"), Some(1));
+    /// assert_eq!(kc.run_action("b"), Some(2));
+    /// ```
+    pub fn bind_cow<T>(&mut self, event: Cow<T>, action: Action<'a, R>)
+        where T: ToOwned<Owned=E> + ?Sized
+    {
+        self.insert(event.into_owned(), action);
+    }
+
+    /// Parse `raw` into an event with `parse` and bind the
+    /// event to `action`. This is for event types that
+    /// cannot always be made from their text form. If
+    /// parsing fails, return its error and bind nothing.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// #[derive(PartialEq, Eq, Hash, Debug)]
+    /// struct Chord { ctrl: bool, key: char }
+    /// #[derive(Debug, PartialEq)]
+    /// struct ParseError;
+    /// fn parse(s: &str) -> Result<Chord, ParseError> {
+    ///     let (ctrl, key) = match s.strip_prefix("Ctrl+") {
+    ///         Some(key) => (true, key),
+    ///         None => (false, s),
+    ///     };
+    ///     let mut chars = key.chars();
+    ///     match (chars.next(), chars.next()) {
+    ///         (Some(key), None) => Ok(Chord { ctrl, key }),
+    ///         _ => Err(ParseError),
+    ///     }
+    /// }
+    /// let save = || "save";
+    /// let mut kc = Bindings::new();
+    /// assert_eq!(kc.try_bind("Ctrl+S", parse, &save), Ok(()));
+    /// assert_eq!(kc.try_bind("Ctrl+", parse, &save), Err(ParseError));
+    /// let chord = Chord { ctrl: true, key: 'S' };
+    /// assert_eq!(kc.run_action(&chord), Some("save"));
+    /// ```
+    pub fn try_bind<T, F, X>(&mut self, raw: &T, parse: F, action: Action<'a, R>)
+                             -> Result<(), X>
+        where T: ?Sized, F: FnOnce(&T) -> Result<E, X>
+    {
+        let event = parse(raw)?;
+        self.insert(event, action);
+        Ok(())
     }
-    
+
+    /// Remove the binding for an event, returning its
+    /// action. Return `None` if no such event is bound.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let one = || 1;
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action(&'a', &one);
+    /// assert!(kc.unbind_action(&'a').is_some());
+    /// assert!(kc.run_action(&'a').is_none());
+    /// assert!(kc.unbind_action(&'a').is_none());
+    /// ```
+    pub fn unbind_action<T>(&mut self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.store.remove(event)
+    }
+
     /// Given an event that is in the bindings, return the
     /// corresponding action unexecuted.  Return
     /// `None` if no such event is bound.
@@ -136,6 +1109,466 @@ impl <'a, E, R> Bindings<'a, E, R>
     pub fn get_action<T>(&self, event: &T) -> Option<Action<'a, R>>
         where E: Borrow<T>, T: Hash + Eq + ?Sized
     {
-        self.0.get(event).and_then(|&action| Some(action))
+        match self.store.get(event) {
+            Some(&action) => Some(action),
+            None => self.aliases.get(event).and_then(|target| self.store.get::<E>(target).copied()),
+        }
+    }
+
+    /// Make `alias` a synonym for `target`: when `alias` is
+    /// not bound itself, looking it up finds the action of
+    /// `target`. Only one alias is followed, so an alias to
+    /// an alias finds nothing. Return an error, changing
+    /// nothing, if the alias would loop back to itself.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let down = || "down";
+    /// let mut kc: Bindings<String, &str> = Bindings::new();
+    /// kc.bind_action("Down", &down);
+    /// kc.bind_alias("j", "Down").unwrap();
+    /// assert_eq!(kc.run_action("j"), Some("down"));
+    /// assert!(kc.bind_alias("k", "k").is_err());
+    /// assert!(kc.bind_alias("Down", "j").is_err());
+    /// assert_eq!(kc.unbind_alias("j"), Some("Down".to_string()));
+    /// assert!(kc.run_action("j").is_none());
+    /// ```
+    pub fn bind_alias<T>(&mut self, alias: &T, target: &T) -> Result<(), AliasLoop>
+        where E: Borrow<T>, T: ToOwned<Owned=E> + Hash + Eq + ?Sized
+    {
+        if alias_loops(&self.aliases, alias, target) {
+            return Err(AliasLoop);
+        }
+        self.aliases.insert(alias.to_owned(), target.to_owned());
+        Ok(())
+    }
+
+    /// Remove the alias `alias`, returning its target.
+    pub fn unbind_alias<T>(&mut self, alias: &T) -> Option<E>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.aliases.remove(alias)
+    }
+
+    /// Run actions until one returns a result satisfying
+    /// `pred`, and return its event and result. Actions are
+    /// tried in no particular order, and every action tried
+    /// is run, so this may run several actions. Return
+    /// `None` if no result satisfies `pred`.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let yell = || "yell";
+    /// let scream = || "scream";
+    /// let mut kc: Bindings<String, &str> = Bindings::new();
+    /// kc.bind_action("X", &yell);
+    /// kc.bind_action("Y", &scream);
+    /// let (event, result) = kc.find_by_result(|&r| r == "scream").unwrap();
+    /// assert_eq!((event.as_str(), result), ("Y", "scream"));
+    /// assert!(kc.find_by_result(|&r| r == "whisper").is_none());
+    /// ```
+    pub fn find_by_result<P>(&self, pred: P) -> Option<(&E, R)>
+        where P: Fn(&R) -> bool
+    {
+        self.store
+            .iter()
+            .map(|(event, action)| (event, action()))
+            .find(|(_, result)| pred(result))
+    }
+
+    /// Run every bound action, as a self-test of all
+    /// commands. Return the results of the actions for which
+    /// `is_err` is false, by event, and the events of the
+    /// actions for which it is true, in no particular order.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let ok = || Ok(1);
+    /// let fail = || Err("broken");
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action(&'a', &ok);
+    /// kc.bind_action(&'b', &fail);
+    /// kc.bind_action(&'c', &fail);
+    /// let (passed, mut failed) = kc.try_run_all(Result::is_err);
+    /// failed.sort();
+    /// assert_eq!(passed.len(), 1);
+    /// assert_eq!(passed[&'a'], Ok(1));
+    /// assert_eq!(failed, vec![&'b', &'c']);
+    /// ```
+    pub fn try_run_all<F>(&self, is_err: F) -> (HashMap<&E, R>, Vec<&E>)
+        where F: Fn(&R) -> bool
+    {
+        let mut passed = HashMap::new();
+        let mut failed = Vec::new();
+        for (event, action) in &self.store {
+            let result = action();
+            if is_err(&result) {
+                failed.push(event);
+            } else {
+                passed.insert(event, result);
+            }
+        }
+        (passed, failed)
+    }
+
+    /// Remove the bindings whose events satisfy `pred`,
+    /// returning them in no particular order.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let act = || ();
+    /// let mut kc: Bindings<String, ()> = Bindings::new();
+    /// for event in &["f1", "f2", "g", "fire", "x"] {
+    ///     kc.bind_action(*event, &act);
+    /// }
+    /// let mut extracted: Vec<String> = kc
+    ///     .extract_if(|event| event.starts_with('f'))
+    ///     .into_iter()
+    ///     .map(|(event, _)| event)
+    ///     .collect();
+    /// extracted.sort();
+    /// assert_eq!(extracted, vec!["f1", "f2", "fire"]);
+    /// assert_eq!(kc.len(), 2);
+    /// assert!(kc.run_action("g").is_some());
+    /// assert!(kc.run_action("f1").is_none());
+    /// ```
+    pub fn extract_if<F>(&mut self, pred: F) -> Vec<(E, Action<'a, R>)>
+        where F: Fn(&E) -> bool
+    {
+        self.store.extract_if(pred)
+    }
+
+    /// Group the events whose actions are the same action,
+    /// by action address, returning only groups of more
+    /// than one event. This is for finding accidental
+    /// synonyms. Groups and the events in each are in no
+    /// particular order. Distinct zero-sized closures may
+    /// share an address, and so be reported together.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// // Capture something, so the closures have distinct
+    /// // addresses.
+    /// let (loud, louder) = ("yell", "scream");
+    /// let yell = move || loud;
+    /// let scream = move || louder;
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action(&'y', &yell);
+    /// kc.bind_action(&'Y', &yell);
+    /// kc.bind_action(&'s', &scream);
+    /// let duplicates = kc.duplicate_actions();
+    /// assert_eq!(duplicates.len(), 1);
+    /// let (address, mut events) = duplicates[0].clone();
+    /// events.sort();
+    /// assert_eq!(events, vec![&'Y', &'y']);
+    /// assert_eq!(address, &yell as *const _ as *const ());
+    /// ```
+    pub fn duplicate_actions(&self) -> Vec<(*const (), Vec<&E>)> {
+        let mut groups: HashMap<usize, Vec<&E>> = HashMap::new();
+        for (event, &action) in &self.store {
+            groups.entry(action_address(action)).or_default().push(event);
+        }
+        groups
+            .into_iter()
+            .filter(|(_, events)| events.len() > 1)
+            .map(|(address, events)| (address as *const (), events))
+            .collect()
+    }
+
+    /// Describe the bindings, one per line in event order:
+    /// the event, then the address of its action. Two
+    /// events with the same address have the same action.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let one = || 1;
+    /// let mut kc = Bindings::new();
+    /// for event in &['c', 'a', 'b'] {
+    ///     kc.bind_action(event, &one);
+    /// }
+    /// let report = kc.report();
+    /// let events: Vec<&str> = report.lines().map(|line| &line[..1]).collect();
+    /// assert_eq!(events, vec!["a", "b", "c"]);
+    /// ```
+    pub fn report(&self) -> String
+        where E: Display + Ord
+    {
+        let mut bindings: Vec<(&E, Action<'a, R>)> =
+            self.store.iter().map(|(event, &action)| (event, action)).collect();
+        bindings.sort_by_key(|&(event, _)| event);
+        let mut report = String::new();
+        for (event, action) in bindings {
+            report += &format!("{}: action at {:#x}\n", event, action_address(action));
+        }
+        report
+    }
+
+    /// Number of bound events, not counting aliases.
+    pub fn len(&self) -> usize {
+        self.store.len()
+    }
+
+    /// True if no events are bound.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return a snapshot of the bindings, in no particular
+    /// order.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let one = || 1;
+    /// let mut kc = Bindings::new();
+    /// for event in &['a', 'b', 'c'] {
+    ///     kc.bind_action(event, &one);
+    /// }
+    /// let mut events: Vec<char> = kc.to_vec()
+    ///     .into_iter()
+    ///     .map(|(event, _)| event)
+    ///     .collect();
+    /// events.sort();
+    /// assert_eq!(events, vec!['a', 'b', 'c']);
+    /// ```
+    pub fn to_vec(&self) -> Vec<(E, Action<'a, R>)>
+        where E: Clone
+    {
+        self.store
+            .iter()
+            .map(|(event, &action)| (event.clone(), action))
+            .collect()
+    }
+
+    /// Call `f` with each event and its action, in no
+    /// particular order. Aliases and the fallback action are
+    /// not visited.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let one = || 1;
+    /// let two = || 2;
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action(&'a', &one);
+    /// kc.bind_action(&'b', &two);
+    /// kc.bind_alias(&'c', &'a').unwrap();
+    /// let mut copy = Bindings::new();
+    /// let mut seen = Vec::new();
+    /// kc.for_each(|&event, action| {
+    ///     copy.bind_action(&event, action);
+    ///     seen.push((event, action()));
+    /// });
+    /// seen.sort();
+    /// assert_eq!(seen, vec![('a', 1), ('b', 2)]);
+    /// assert_eq!(copy.run_action(&'b'), Some(2));
+    /// ```
+    pub fn for_each<F>(&self, mut f: F)
+        where F: FnMut(&E, Action<'a, R>)
+    {
+        for (event, &action) in &self.store {
+            f(event, action);
+        }
+    }
+
+    /// Return new bindings holding just the bindings for
+    /// `keys`. Keys that are not bound are skipped.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let one = || 1;
+    /// let mut kc = Bindings::new();
+    /// for event in &["a", "b", "c", "d"] {
+    ///     kc.bind_action(*event, &one);
+    /// }
+    /// let sub: Bindings<String, usize> = kc.subset(&["b", "d", "z"]);
+    /// assert!(sub.run_action("b").is_some());
+    /// assert!(sub.run_action("d").is_some());
+    /// assert!(sub.run_action("a").is_none());
+    /// assert!(sub.run_action("c").is_none());
+    /// assert_eq!(sub.to_vec().len(), 2);
+    /// ```
+    pub fn subset<T>(&self, keys: &[&T]) -> Bindings<'a, E, R>
+        where E: Borrow<T> + Clone, T: Hash + Eq + ?Sized
+    {
+        let mut sub = Bindings::new();
+        for &key in keys {
+            if let Some((event, &action)) = self.store.get_key_value(key) {
+                sub.store.insert(event.clone(), action);
+            }
+        }
+        sub
+    }
+
+    /// Return the keys of `required` that are not bound, in
+    /// the order given, for checking a loaded keymap in
+    /// tests or at startup. Aliases count as bound; the
+    /// fallback action does not.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let act = || ();
+    /// let mut kc: Bindings<String, ()> = Bindings::new();
+    /// kc.bind_action("save", &act);
+    /// kc.bind_action("quit", &act);
+    /// kc.set_fallback(&act);
+    /// assert_eq!(kc.missing_required(&["save", "open", "quit"]), vec!["open"]);
+    /// assert!(kc.missing_required(&["save", "quit"]).is_empty());
+    /// ```
+    pub fn missing_required<'r, T>(&self, required: &[&'r T]) -> Vec<&'r T>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        required
+            .iter()
+            .filter(|&&key| self.get_action(key).is_none())
+            .cloned()
+            .collect()
+    }
+
+    /// Return the events bound in either `self` or `other`,
+    /// for comparing keymaps. Aliases are not included.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let act = || ();
+    /// let mut left: Bindings<&str, ()> = Bindings::new();
+    /// left.bind_action(&"save", &act);
+    /// left.bind_action(&"quit", &act);
+    /// let mut right = Bindings::new();
+    /// right.bind_action(&"quit", &act);
+    /// right.bind_action(&"open", &act);
+    /// let mut union: Vec<_> = left.key_union(&right).into_iter().collect();
+    /// union.sort();
+    /// assert_eq!(union, vec![&"open", &"quit", &"save"]);
+    /// let common: Vec<_> = left.key_intersection(&right).into_iter().collect();
+    /// assert_eq!(common, vec![&"quit"]);
+    /// ```
+    pub fn key_union<'b>(&'b self, other: &'b Self) -> HashSet<&'b E> {
+        self.store.keys().chain(other.store.keys()).collect()
+    }
+
+    /// Return the events bound in both `self` and `other`.
+    /// Aliases are not included. See `key_union()`.
+    pub fn key_intersection<'b>(&'b self, other: &'b Self) -> HashSet<&'b E> {
+        self.store.keys().filter(|&event| other.store.contains_key::<E>(event)).collect()
+    }
+
+    /// Return the bound events whose text starts with
+    /// `prefix`, sorted and without duplicates, for
+    /// completing partially typed event names. If
+    /// `ignore_case` is set, case is ignored when matching.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let one = || 1;
+    /// let mut kc = Bindings::new();
+    /// for event in &["Save", "save-as", "quit", "scroll"] {
+    ///     kc.bind_action(*event, &one);
+    /// }
+    /// assert_eq!(kc.complete_event("s", false), vec!["save-as", "scroll"]);
+    /// assert_eq!(kc.complete_event("SA", true), vec!["Save", "save-as"]);
+    /// ```
+    pub fn complete_event(&self, prefix: &str, ignore_case: bool) -> Vec<String>
+        where E: Display
+    {
+        completions(self.store.keys().map(E::to_string), prefix, ignore_case)
+    }
+}
+
+impl<'a, R> Bindings<'a, u32, R>
+    where R: 'a
+{
+    /// Overwrite or create a binding for an integer code,
+    /// such as a hardware scancode, without going through
+    /// the `Borrow`/`ToOwned` conversions of
+    /// `bind_action()`.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, Bindings};
+    /// let codes = [0x1e_u32, 0x30, 0x2e, 0xe05b];
+    /// let actions: [Action<usize>; 4] = [&|| 1, &|| 2, &|| 3, &|| 4];
+    /// let mut fast = Bindings::new();
+    /// let mut generic = Bindings::new();
+    /// for (&code, &action) in codes.iter().zip(actions.iter()) {
+    ///     fast.bind_code(code, action);
+    ///     generic.bind_action(&code, action);
+    /// }
+    /// for code in codes.iter().chain(&[0, 0x1f]) {
+    ///     assert_eq!(fast.run_code(*code), generic.run_action(code));
+    /// }
+    /// ```
+    pub fn bind_code(&mut self, code: u32, action: Action<'a, R>) {
+        self.insert(code, action);
+    }
+
+    /// Run the action for an integer code and return the
+    /// result, exactly as `run_action()` does, following
+    /// aliases and falling back to the fallback action.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, Bindings};
+    /// let jump: Action<&str> = &|| "jump";
+    /// let other: Action<&str> = &|| "other";
+    /// let mut kc = Bindings::new();
+    /// kc.bind_code(0x39, jump);
+    /// kc.bind_alias(&0x1c, &0x39).unwrap();
+    /// kc.set_fallback(other);
+    /// assert_eq!(kc.run_code(0x39), Some("jump"));
+    /// assert_eq!(kc.run_code(0x1c), Some("jump"));
+    /// assert_eq!(kc.run_code(0x01), Some("other"));
+    /// ```
+    pub fn run_code(&self, code: u32) -> Option<R> {
+        self.run_action(&code)
+    }
+}
+
+impl<'a, R> Bindings<'a, String, R>
+    where R: 'a
+{
+    /// Return the number of bound events starting with
+    /// `prefix`, such as the commands a plugin registered
+    /// under its namespace. Aliases are not counted.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let act = || ();
+    /// let mut kc: Bindings<String, ()> = Bindings::new();
+    /// kc.bind_action("git:a", &act);
+    /// kc.bind_action("git:b", &act);
+    /// kc.bind_action("vim:x", &act);
+    /// assert_eq!(kc.count_prefix("git:"), 2);
+    /// assert_eq!(kc.count_prefix("emacs:"), 0);
+    /// assert_eq!(kc.count_prefix(""), 3);
+    /// ```
+    pub fn count_prefix(&self, prefix: &str) -> usize {
+        self.store.keys().filter(|event| event.starts_with(prefix)).count()
     }
 }
@@ -0,0 +1,112 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Bindings whose events can match by custom rules, for
+//! structured events with fields that lookup should
+//! ignore, such as timestamps or repeat counts.
+
+use std::borrow::{Borrow, ToOwned};
+use std::hash::Hash;
+
+use {Action, Bindings};
+
+/// A rule saying whether an input event matches a bound
+/// one. Any `Fn(&E, &E) -> bool` closure is a matcher.
+pub trait Matcher<E> {
+    /// True if `input` should run the action bound to
+    /// `stored`.
+    fn matches(&self, stored: &E, input: &E) -> bool;
+}
+
+impl<E, F> Matcher<E> for F
+    where F: Fn(&E, &E) -> bool
+{
+    fn matches(&self, stored: &E, input: &E) -> bool {
+        self(stored, input)
+    }
+}
+
+/// A `MatchingBindings` object wraps a `Bindings`, looking
+/// an event up exactly first and, if that misses, scanning
+/// the bound events for one that the matcher says matches.
+/// If several match, which wins is unspecified. The scan
+/// takes time proportional to the number of bindings.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{Bindings, MatchingBindings};
+/// #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// struct Key {
+///     code: u32,
+///     time: u64,
+/// }
+/// let fire = || "fire";
+/// let mut kc = MatchingBindings::new(Bindings::new(), |stored: &Key, input: &Key| {
+///     stored.code == input.code
+/// });
+/// kc.bind_action(&Key { code: 33, time: 0 }, &fire);
+/// assert_eq!(kc.run_action(&Key { code: 33, time: 1234 }), Some("fire"));
+/// assert!(kc.run_action(&Key { code: 34, time: 0 }).is_none());
+/// assert!(kc.bindings().run_action(&Key { code: 33, time: 1234 }).is_none());
+/// ```
+pub struct MatchingBindings<'a, E, R, M>
+    where E: Hash + Eq, R: 'a, M: Matcher<E>
+{
+    bindings: Bindings<'a, E, R>,
+    matcher: M,
+}
+
+impl<'a, E, R, M> MatchingBindings<'a, E, R, M>
+    where E: Hash + Eq, R: 'a, M: Matcher<E>
+{
+    /// Look up events of `bindings` using `matcher` when
+    /// exact lookup misses.
+    pub fn new(bindings: Bindings<'a, E, R>, matcher: M) -> Self {
+        MatchingBindings { bindings, matcher }
+    }
+
+    /// The wrapped bindings.
+    pub fn bindings(&self) -> &Bindings<'a, E, R> {
+        &self.bindings
+    }
+
+    /// Stop matching, returning the bindings.
+    pub fn into_inner(self) -> Bindings<'a, E, R> {
+        self.bindings
+    }
+
+    /// Overwrite or create a binding.
+    pub fn bind_action<T>(&mut self, event: &T, action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.bindings.bind_action(event, action);
+    }
+
+    /// Remove the exact binding for `event`, returning its
+    /// action.
+    pub fn unbind_action(&mut self, event: &E) -> Option<Action<'a, R>> {
+        self.bindings.unbind_action::<E>(event)
+    }
+
+    /// Given an event that is bound or matches a bound
+    /// event, run the corresponding action and return the
+    /// result.  Return `None` if there is no such event.
+    pub fn run_action(&self, event: &E) -> Option<R> {
+        self.get_action(event).map(|action| action())
+    }
+
+    /// Given an event that is bound or matches a bound
+    /// event, return the corresponding action unexecuted.
+    /// Return `None` if there is no such event.
+    pub fn get_action(&self, event: &E) -> Option<Action<'a, R>> {
+        self.bindings.get_action::<E>(event).or_else(|| {
+            self.bindings.store
+                .iter()
+                .find(|&(stored, _)| self.matcher.matches(stored, event))
+                .map(|(_, &action)| action)
+        })
+    }
+}
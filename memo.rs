@@ -0,0 +1,245 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Caching of action results, for expensive actions that
+//! are dispatched over and over.
+//!
+//! **Memoizing is only correct for pure actions**: actions
+//! whose result depends on nothing that can change and that
+//! have no side effects worth repeating. A cached result is
+//! returned without running the action at all. Use
+//! `MemoBindings::verify()` while testing to catch actions
+//! that are not pure.
+
+use std::borrow::{Borrow, ToOwned};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use {Action, Bindings};
+
+/// A `MemoBindings` object wraps a `Bindings`, caching the
+/// result of each event's action and returning a clone of
+/// it on later dispatches. It can be limited to a number of
+/// cached results, evicting the least recently used.
+///
+/// # Examples:
+///
+/// ```
+/// use std::cell::Cell;
+/// use kbehdz::{Bindings, MemoBindings};
+/// let runs = Cell::new(0);
+/// let help = || { runs.set(runs.get() + 1); "help text".to_string() };
+/// let layout = || { runs.set(runs.get() + 1); "layout".to_string() };
+/// let mut kc = Bindings::new();
+/// kc.bind_action(&'?', &help);
+/// kc.bind_action(&'l', &layout);
+/// let kc = MemoBindings::with_lru_bound(kc, 1);
+/// assert_eq!(kc.run_action(&'?').unwrap(), "help text");
+/// assert_eq!(kc.run_action(&'?').unwrap(), "help text");
+/// assert_eq!(runs.get(), 1);
+/// kc.run_action(&'l');
+/// kc.run_action(&'?');
+/// assert_eq!(runs.get(), 3);
+/// kc.invalidate_all();
+/// kc.run_action(&'?');
+/// assert_eq!(runs.get(), 4);
+/// ```
+pub struct MemoBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    bindings: Bindings<'a, E, R>,
+    cache: RefCell<HashMap<E, (R, u64)>>,
+    bound: Option<usize>,
+    clock: Cell<u64>,
+    verify: Option<fn(&R, &R)>,
+}
+
+impl<'a, E, R> MemoBindings<'a, E, R>
+    where E: Hash + Eq + Clone, R: Clone + 'a
+{
+    /// Start caching the results of `bindings`, without
+    /// limit.
+    pub fn new(bindings: Bindings<'a, E, R>) -> Self {
+        MemoBindings {
+            bindings,
+            cache: RefCell::new(HashMap::new()),
+            bound: None,
+            clock: Cell::new(0),
+            verify: None,
+        }
+    }
+
+    /// Start caching the results of `bindings`, keeping at
+    /// most `bound` of them and evicting the least recently
+    /// used.
+    pub fn with_lru_bound(bindings: Bindings<'a, E, R>, bound: usize) -> Self {
+        MemoBindings {
+            bound: Some(bound),
+            ..MemoBindings::new(bindings)
+        }
+    }
+
+    /// Run the action even when a result is cached, and
+    /// panic if its result differs from the cached one. This
+    /// is for catching impure actions while testing.
+    ///
+    /// # Examples:
+    ///
+    /// ```should_panic
+    /// use std::cell::Cell;
+    /// use kbehdz::{Bindings, MemoBindings};
+    /// let count = Cell::new(0);
+    /// let impure = || { count.set(count.get() + 1); count.get() };
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action(&'i', &impure);
+    /// let mut kc = MemoBindings::new(kc);
+    /// kc.verify();
+    /// kc.run_action(&'i');
+    /// kc.run_action(&'i');
+    /// ```
+    pub fn verify(&mut self)
+        where R: PartialEq + Debug
+    {
+        self.verify = Some(|cached, fresh| {
+            assert_eq!(cached, fresh, "memoized action is not pure");
+        });
+    }
+
+    /// The wrapped bindings.
+    pub fn bindings(&self) -> &Bindings<'a, E, R> {
+        &self.bindings
+    }
+
+    /// Stop caching, returning the bindings.
+    pub fn into_inner(self) -> Bindings<'a, E, R> {
+        self.bindings
+    }
+
+    /// Overwrite or create a binding, forgetting any cached
+    /// result for the event.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::cell::Cell;
+    /// use kbehdz::{Bindings, MemoBindings};
+    /// let runs = Cell::new(0);
+    /// let old = || { runs.set(runs.get() + 1); vec![1] };
+    /// let new = || { runs.set(runs.get() + 1); vec![2] };
+    /// let mut kc = MemoBindings::new(Bindings::new());
+    /// kc.bind_action(&'x', &old);
+    /// assert_eq!(kc.run_action(&'x'), Some(vec![1]));
+    /// assert_eq!(kc.run_action(&'x'), Some(vec![1]));
+    /// assert_eq!(runs.get(), 1);
+    /// kc.bind_action(&'x', &new);
+    /// assert_eq!(kc.run_action(&'x'), Some(vec![2]));
+    /// assert_eq!(runs.get(), 2);
+    /// kc.unbind_action(&'x');
+    /// assert!(kc.run_action(&'x').is_none());
+    /// ```
+    pub fn bind_action<T>(&mut self, event: &T, action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + Hash + Eq + ?Sized
+    {
+        self.invalidate(event);
+        self.bindings.bind_action(event, action);
+    }
+
+    /// Remove the binding for `event` and forget any cached
+    /// result for it, returning its action.
+    pub fn unbind_action<T>(&mut self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.invalidate(event);
+        self.bindings.unbind_action(event)
+    }
+
+    /// Forget the cached result for `event`, and for any
+    /// alias of it, if any.
+    pub fn invalidate<T>(&self, event: &T)
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        let mut cache = self.cache.borrow_mut();
+        cache.remove(event);
+        let aliases = &self.bindings.aliases;
+        cache.retain(|cached, _| {
+            aliases.get::<E>(cached).is_none_or(|target| target.borrow() != event)
+        });
+    }
+
+    /// Forget all cached results.
+    pub fn invalidate_all(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    /// Next tick of the use clock.
+    fn tick(&self) -> u64 {
+        let now = self.clock.get() + 1;
+        self.clock.set(now);
+        now
+    }
+
+    /// Given an event that is in the bindings, directly or
+    /// by an alias, return a clone of its cached result,
+    /// running the action and caching its result if there
+    /// is none. Return `None` if no such event is bound.
+    /// The fallback action is not run, since its result is
+    /// not worth caching for every unbound event.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::cell::Cell;
+    /// use kbehdz::{Bindings, MemoBindings};
+    /// let runs = Cell::new(0);
+    /// let old = || { runs.set(runs.get() + 1); "old" };
+    /// let new = || { runs.set(runs.get() + 1); "new" };
+    /// let mut kc: Bindings<String, &str> = Bindings::new();
+    /// kc.bind_alias("H", "h").unwrap();
+    /// let mut kc = MemoBindings::new(kc);
+    /// kc.bind_action("h", &old);
+    /// assert_eq!(kc.run_action("H"), Some("old"));
+    /// assert_eq!(kc.run_action("H"), Some("old"));
+    /// assert_eq!(runs.get(), 1);
+    /// kc.bind_action("h", &new);
+    /// assert_eq!(kc.run_action("H"), Some("new"));
+    /// assert_eq!(runs.get(), 2);
+    /// ```
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: ToOwned<Owned=E> + Hash + Eq + ?Sized
+    {
+        let now = self.tick();
+        let cached = self.cache.borrow_mut().get_mut(event).map(|entry| {
+            entry.1 = now;
+            entry.0.clone()
+        });
+        let action = self.bindings.get_action(event)?;
+        if let Some(result) = cached {
+            if let Some(verify) = self.verify {
+                verify(&result, &action());
+            }
+            return Some(result);
+        }
+        let result = action();
+        let mut cache = self.cache.borrow_mut();
+        if let Some(bound) = self.bound {
+            if bound == 0 {
+                return Some(result);
+            }
+            if cache.len() >= bound {
+                let oldest = cache
+                    .iter()
+                    .min_by_key(|&(_, &(_, used))| used)
+                    .map(|(event, _)| event.clone());
+                if let Some(oldest) = oldest {
+                    cache.remove::<E>(&oldest);
+                }
+            }
+        }
+        cache.insert(event.to_owned(), (result.clone(), now));
+        Some(result)
+    }
+}
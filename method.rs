@@ -0,0 +1,199 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Binding events to methods of shared objects: the usual
+//! "call a method on my app state" action, without a
+//! closure per binding.
+//!
+//! For `bind_method()`, if the object is already in use
+//! when the action runs, as when an action dispatches
+//! another event bound to the same object, the action does
+//! not call the method but returns `MethodError::Busy`.
+//! For `bind_locked()`, the action waits while another
+//! thread holds the lock, but a re-entrant dispatch on the
+//! thread already holding it returns `MethodError::Busy`
+//! rather than deadlocking.
+
+use std::borrow::{Borrow, ToOwned};
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::hash::Hash;
+use std::rc::Rc;
+#[cfg(feature = "sync")]
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+#[cfg(feature = "sync")]
+use std::thread::{self, ThreadId};
+
+use ScopedBindings;
+#[cfg(feature = "sync")]
+use SharedBindings;
+
+/// Error from an action made by `bind_method()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MethodError {
+    /// The object was already borrowed, or locked by the
+    /// same thread.
+    Busy,
+    /// The object's lock was poisoned by a panic.
+    Poisoned,
+}
+
+impl Display for MethodError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MethodError::Busy => write!(f, "target object is already in use"),
+            MethodError::Poisoned => write!(f, "target object lock is poisoned"),
+        }
+    }
+}
+
+impl Error for MethodError {}
+
+/// Targets locked by `bind_locked()` actions, by address,
+/// with the thread holding each.
+#[cfg(feature = "sync")]
+static OWNERS: Mutex<Vec<(usize, ThreadId)>> = Mutex::new(Vec::new());
+
+/// The owners list, whatever a panicking holder left it as.
+#[cfg(feature = "sync")]
+fn owners() -> MutexGuard<'static, Vec<(usize, ThreadId)>> {
+    OWNERS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A lock on a `bind_locked()` target, recorded in
+/// `OWNERS` while it is held.
+#[cfg(feature = "sync")]
+struct Held<'m, M: 'm> {
+    target: MutexGuard<'m, M>,
+    owner: (usize, ThreadId),
+}
+
+#[cfg(feature = "sync")]
+impl<'m, M> Held<'m, M> {
+    /// Lock `target`, waiting for other threads, or fail
+    /// with `MethodError::Busy` if this thread holds it.
+    fn lock(target: &'m Mutex<M>) -> Result<Self, MethodError> {
+        let owner = (target as *const Mutex<M> as usize, thread::current().id());
+        if owners().contains(&owner) {
+            return Err(MethodError::Busy);
+        }
+        let target = target.lock().map_err(|_| MethodError::Poisoned)?;
+        owners().push(owner);
+        Ok(Held { target, owner })
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<'m, M> Drop for Held<'m, M> {
+    fn drop(&mut self) {
+        let mut owners = owners();
+        if let Some(i) = owners.iter().position(|&owner| owner == self.owner) {
+            owners.swap_remove(i);
+        }
+    }
+}
+
+impl<'a, E, X> ScopedBindings<'a, E, Result<X, MethodError>>
+    where E: Hash + Eq, X: 'a
+{
+    /// Overwrite or create a binding to a call of `method`
+    /// on `target`.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use kbehdz::{MethodError, OwnedBindings};
+    /// struct Counter(u32);
+    /// impl Counter {
+    ///     fn bump(&mut self) -> u32 {
+    ///         self.0 += 1;
+    ///         self.0
+    ///     }
+    /// }
+    /// let counter = Rc::new(RefCell::new(Counter(0)));
+    /// let mut kc = OwnedBindings::new();
+    /// kc.bind_method("+", Rc::clone(&counter), Counter::bump);
+    /// assert_eq!(kc.run_action("+"), Some(Ok(1)));
+    /// assert_eq!(kc.run_action("+"), Some(Ok(2)));
+    /// let held = counter.borrow_mut();
+    /// assert_eq!(kc.run_action("+"), Some(Err(MethodError::Busy)));
+    /// drop(held);
+    /// assert_eq!(counter.borrow().0, 2);
+    /// ```
+    pub fn bind_method<T, M>(&mut self, event: &T, target: Rc<RefCell<M>>, method: fn(&mut M) -> X)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized, M: 'a
+    {
+        self.bind_action(event, Box::new(move || {
+            let mut target = target.try_borrow_mut().map_err(|_| MethodError::Busy)?;
+            Ok(method(&mut target))
+        }));
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<E, X> SharedBindings<E, Result<X, MethodError>>
+    where E: Hash + Eq, X: 'static
+{
+    /// Overwrite or create a binding to a call of `method`
+    /// on `target`, locking it for the call. The action
+    /// waits while another thread holds the lock. It
+    /// returns `MethodError::Busy` if the calling thread
+    /// already holds the lock, as when the method
+    /// dispatches an event bound to the same target, and
+    /// `MethodError::Poisoned` if the lock is poisoned.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use kbehdz::SharedBindings;
+    /// let log = Arc::new(Mutex::new(Vec::new()));
+    /// let mut kc = SharedBindings::new();
+    /// kc.bind_locked("s", Arc::clone(&log), |log: &mut Vec<&str>| log.push("save"));
+    /// assert_eq!(kc.run_action("s"), Some(Ok(())));
+    ///
+    /// let held = log.lock().unwrap();
+    /// let saver = {
+    ///     let kc = kc.clone();
+    ///     thread::spawn(move || kc.run_action("s"))
+    /// };
+    /// thread::sleep(Duration::from_millis(10));
+    /// drop(held);
+    /// assert_eq!(saver.join().unwrap(), Some(Ok(())));
+    /// assert_eq!(*log.lock().unwrap(), vec!["save", "save"]);
+    /// ```
+    ///
+    /// A method that dispatches its own event gets `Busy`:
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use kbehdz::{MethodError, SharedBindings};
+    /// struct App {
+    ///     keys: Option<SharedBindings<String, Result<String, MethodError>>>,
+    /// }
+    /// fn again(app: &mut App) -> String {
+    ///     format!("{:?}", app.keys.as_ref().unwrap().run_action("r"))
+    /// }
+    /// let app = Arc::new(Mutex::new(App { keys: None }));
+    /// let mut kc = SharedBindings::new();
+    /// kc.bind_locked("r", Arc::clone(&app), again);
+    /// app.lock().unwrap().keys = Some(kc.clone());
+    /// assert_eq!(kc.run_action("r"), Some(Ok("Some(Err(Busy))".to_string())));
+    /// assert!(app.try_lock().is_ok());
+    /// ```
+    pub fn bind_locked<T, M>(&mut self, event: &T, target: Arc<Mutex<M>>, method: fn(&mut M) -> X)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized, M: Send + 'static
+    {
+        self.bind_fn(event, move || {
+            let mut held = Held::lock(&target)?;
+            Ok(method(&mut held.target))
+        });
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Bindings that tolerate different spellings of an event,
+//! such as `"Ctrl+S"`, `"ctrl+s"` and `"C-s"`, by turning
+//! each into a canonical key.
+
+use std::hash::Hash;
+
+use {Action, Bindings};
+
+/// A raw event that can be turned into a canonical event of
+/// type `E`. Spellings of the same event should give equal
+/// canonical events.
+pub trait NormalizeKey<E> {
+    /// The canonical event.
+    fn normalize(&self) -> E;
+}
+
+/// A `NormalizingBindings` object wraps a `Bindings` of
+/// canonical events, normalizing each raw event both when
+/// binding and when looking up.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{Bindings, NormalizeKey, NormalizingBindings};
+/// struct Chord<'s>(&'s str);
+/// impl<'s> NormalizeKey<String> for Chord<'s> {
+///     fn normalize(&self) -> String {
+///         let lower = self.0.to_lowercase();
+///         let (modifier, key) = lower.split_at(lower.len() - 1);
+///         match modifier {
+///             "ctrl+" | "c-" => format!("C-{}", key),
+///             _ => lower.clone(),
+///         }
+///     }
+/// }
+/// let save = || "save";
+/// let mut kc = NormalizingBindings::new(Bindings::new());
+/// kc.bind_action(&Chord("Ctrl+S"), &save);
+/// assert_eq!(kc.run_action(&Chord("c-s")), Some("save"));
+/// assert_eq!(kc.run_action(&Chord("ctrl+s")), Some("save"));
+/// assert_eq!(kc.bindings().run_action("C-s"), Some("save"));
+/// assert!(kc.run_action(&Chord("c-x")).is_none());
+/// ```
+pub struct NormalizingBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    bindings: Bindings<'a, E, R>,
+}
+
+impl<'a, E, R> NormalizingBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Normalize events for `bindings`, whose events should
+    /// already be canonical.
+    pub fn new(bindings: Bindings<'a, E, R>) -> Self {
+        NormalizingBindings { bindings }
+    }
+
+    /// The wrapped bindings, with canonical events.
+    pub fn bindings(&self) -> &Bindings<'a, E, R> {
+        &self.bindings
+    }
+
+    /// Stop normalizing, returning the bindings.
+    pub fn into_inner(self) -> Bindings<'a, E, R> {
+        self.bindings
+    }
+
+    /// Overwrite or create a binding for the canonical form
+    /// of `event`.
+    pub fn bind_action<T>(&mut self, event: &T, action: Action<'a, R>)
+        where T: NormalizeKey<E> + ?Sized
+    {
+        self.bindings.insert(event.normalize(), action);
+    }
+
+    /// Remove the binding for the canonical form of
+    /// `event`, returning its action.
+    pub fn unbind_action<T>(&mut self, event: &T) -> Option<Action<'a, R>>
+        where T: NormalizeKey<E> + ?Sized
+    {
+        self.bindings.unbind_action::<E>(&event.normalize())
+    }
+
+    /// Given an event whose canonical form is in the
+    /// bindings, run the corresponding action and return
+    /// the result.  Return `None` if no such event is bound.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where T: NormalizeKey<E> + ?Sized
+    {
+        self.bindings.run_action::<E>(&event.normalize())
+    }
+
+    /// Given an event whose canonical form is in the
+    /// bindings, return the corresponding action
+    /// unexecuted.  Return `None` if no such event is bound.
+    pub fn get_action<T>(&self, event: &T) -> Option<Action<'a, R>>
+        where T: NormalizeKey<E> + ?Sized
+    {
+        self.bindings.get_action::<E>(&event.normalize())
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Notification of changes to bindings, for keeping
+//! derived views such as help tables up to date.
+
+use std::borrow::{Borrow, ToOwned};
+use std::hash::Hash;
+
+use store::Store;
+use {Action, Bindings};
+
+/// A change to an `ObservedBindings`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindingChange<E> {
+    /// An unbound event was bound.
+    Bound {
+        /// The event.
+        event: E,
+    },
+    /// A bound event was unbound.
+    Unbound {
+        /// The event.
+        event: E,
+    },
+    /// A bound event was bound to a new action.
+    Rebound {
+        /// The event.
+        event: E,
+    },
+    /// All bindings and aliases were removed.
+    Cleared,
+}
+
+/// Identifies a listener of an `ObservedBindings`, for
+/// removing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(u64);
+
+/// Type of listeners.
+type Listener<'a, E> = Box<dyn Fn(&BindingChange<E>) + 'a>;
+
+/// An `ObservedBindings` object wraps a `Bindings`,
+/// telling its listeners about each change. Listeners are
+/// called in the order they were added, after the change
+/// has been made. They cannot change the bindings.
+///
+/// # Examples:
+///
+/// ```
+/// use std::cell::RefCell;
+/// use kbehdz::{BindingChange, Bindings, ObservedBindings};
+/// let fire = || ();
+/// let changes = RefCell::new(Vec::new());
+/// let mut kc = ObservedBindings::new(Bindings::new());
+/// let id = kc.on_change(|change: &BindingChange<char>| {
+///     changes.borrow_mut().push(change.clone())
+/// });
+/// kc.bind_action(&'f', &fire);
+/// kc.bind_action(&'f', &fire);
+/// kc.unbind_action(&'f');
+/// kc.unbind_action(&'f');
+/// kc.clear();
+/// assert!(kc.remove_listener(id));
+/// kc.bind_action(&'g', &fire);
+/// assert_eq!(*changes.borrow(), vec![
+///     BindingChange::Bound { event: 'f' },
+///     BindingChange::Rebound { event: 'f' },
+///     BindingChange::Unbound { event: 'f' },
+///     BindingChange::Cleared,
+/// ]);
+/// ```
+pub struct ObservedBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    bindings: Bindings<'a, E, R>,
+    listeners: Vec<(ListenerId, Listener<'a, E>)>,
+    next_id: u64,
+}
+
+impl<'a, E, R> ObservedBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Start observing `bindings`, with no listeners.
+    pub fn new(bindings: Bindings<'a, E, R>) -> Self {
+        ObservedBindings {
+            bindings,
+            listeners: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// The observed bindings.
+    pub fn bindings(&self) -> &Bindings<'a, E, R> {
+        &self.bindings
+    }
+
+    /// Stop observing, returning the bindings.
+    pub fn into_inner(self) -> Bindings<'a, E, R> {
+        self.bindings
+    }
+
+    /// Add a listener to be called with each change,
+    /// returning its id.
+    pub fn on_change<F>(&mut self, listener: F) -> ListenerId
+        where F: Fn(&BindingChange<E>) + 'a
+    {
+        let id = ListenerId(self.next_id);
+        self.next_id += 1;
+        self.listeners.push((id, Box::new(listener)));
+        id
+    }
+
+    /// Remove the listener with id `id`. Return false if
+    /// there is no such listener.
+    pub fn remove_listener(&mut self, id: ListenerId) -> bool {
+        let before = self.listeners.len();
+        self.listeners.retain(|&(i, _)| i != id);
+        self.listeners.len() < before
+    }
+
+    /// Tell the listeners about `change`.
+    fn notify(&self, change: BindingChange<E>) {
+        for (_, listener) in &self.listeners {
+            listener(&change);
+        }
+    }
+
+    /// Overwrite or create a binding, reporting it as
+    /// `Bound` or `Rebound`.
+    pub fn bind_action<T>(&mut self, event: &T, action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        let owned = event.to_owned();
+        let rebound = self.bindings.store.contains_key::<E>(&owned);
+        self.bindings.insert(owned, action);
+        let event = event.to_owned();
+        if rebound {
+            self.notify(BindingChange::Rebound { event });
+        } else {
+            self.notify(BindingChange::Bound { event });
+        }
+    }
+
+    /// Remove the binding for `event`, returning its action
+    /// and reporting it as `Unbound` if there was one.
+    pub fn unbind_action<T>(&mut self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: ToOwned<Owned=E> + Hash + Eq + ?Sized
+    {
+        let action = self.bindings.unbind_action(event)?;
+        self.notify(BindingChange::Unbound { event: event.to_owned() });
+        Some(action)
+    }
+
+    /// Remove every binding and alias, reporting it as
+    /// `Cleared`.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Bindings, ObservedBindings};
+    /// let fire = || "fire";
+    /// let mut kc: Bindings<String, &str> = Bindings::new();
+    /// kc.bind_action("f", &fire);
+    /// kc.bind_alias("F", "f").unwrap();
+    /// let mut kc = ObservedBindings::new(kc);
+    /// kc.clear();
+    /// kc.bind_action("f", &fire);
+    /// assert!(kc.run_action("F").is_none());
+    /// ```
+    pub fn clear(&mut self) {
+        self.bindings.store = Store::new();
+        self.bindings.aliases.clear();
+        self.notify(BindingChange::Cleared);
+    }
+
+    /// Given an event that is in the bindings, run the
+    /// corresponding action and return the result.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.bindings.run_action(event)
+    }
+}
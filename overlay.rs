@@ -0,0 +1,99 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Read-through overlays on `Bindings`, for transient
+//! overrides that must leave the base bindings untouched.
+
+use std::borrow::{Borrow, ToOwned};
+use std::hash::Hash;
+
+use {Action, Bindings};
+
+/// An `Overlay` is a view of some base `Bindings` with
+/// bindings of its own that take precedence. Changing the
+/// overlay never changes the base.
+pub struct Overlay<'b, 'a: 'b, E, R>
+    where E: Hash + Eq + 'b, R: 'a
+{
+    base: &'b Bindings<'a, E, R>,
+    own: Bindings<'a, E, R>,
+}
+
+impl<'b, 'a, E, R> Overlay<'b, 'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// The base bindings.
+    pub fn base(&self) -> &'b Bindings<'a, E, R> {
+        self.base
+    }
+
+    /// The overlay's own bindings.
+    pub fn own(&self) -> &Bindings<'a, E, R> {
+        &self.own
+    }
+
+    /// Overwrite or create a binding in the overlay.
+    pub fn bind_action<T>(&mut self, event: &T, action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.own.bind_action(event, action);
+    }
+
+    /// Remove the overlay's binding for `event`, revealing
+    /// any base binding, and return its action.
+    pub fn unbind_action<T>(&mut self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.own.unbind_action(event)
+    }
+
+    /// Given an event that is in the overlay or the base,
+    /// run the corresponding action and return the result,
+    /// preferring the overlay.  Return `None` if no such
+    /// event is bound.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.get_action(event).map(|action| action())
+    }
+
+    /// Given an event that is in the overlay or the base,
+    /// return the corresponding action unexecuted,
+    /// preferring the overlay.  Return `None` if no such
+    /// event is bound.
+    pub fn get_action<T>(&self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.own.get_action(event).or_else(|| self.base.get_action(event))
+    }
+}
+
+impl<'a, E, R> Bindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Make an empty `Overlay` over these bindings.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let walk = || "walk";
+    /// let run = || "run";
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action(&'w', &walk);
+    /// let mut session = kc.overlay();
+    /// session.bind_action(&'w', &run);
+    /// assert_eq!(session.run_action(&'w'), Some("run"));
+    /// assert_eq!(kc.run_action(&'w'), Some("walk"));
+    /// session.unbind_action(&'w');
+    /// assert_eq!(session.run_action(&'w'), Some("walk"));
+    /// ```
+    pub fn overlay<'b>(&'b self) -> Overlay<'b, 'a, E, R> {
+        Overlay {
+            base: self,
+            own: Bindings::new(),
+        }
+    }
+}
@@ -0,0 +1,286 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Bindings that own their actions, for handing keymaps
+//! across API boundaries without a lifetime attached.
+//!
+//! `ScopedBindings` boxes each action separately, so its
+//! lifetime `'a` is only a lower bound that each action
+//! must meet, not one shared borrow: a `'static` table of
+//! default actions can be mixed with closures borrowing the
+//! state of one screen, and the screen's closures go away
+//! when they are unbound. `OwnedBindings` is
+//! `ScopedBindings` with `'static` actions, so it can live
+//! in long-lived structures.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::borrow::{Borrow, ToOwned};
+use std::iter::FromIterator;
+
+use {diverging, Action, Bindings};
+
+/// A `ScopedBindings` object manages bindings between
+/// events and boxed actions it owns, each living at least
+/// as long as `'a`.
+pub struct ScopedBindings<'a, E, R>(HashMap<E, Box<dyn Fn() -> R + 'a>>)
+    where E: Hash + Eq, R: 'a;
+
+/// An `OwnedBindings` object manages bindings between
+/// events and boxed `'static` actions it owns. It has no
+/// lifetime parameter, so it can live in long-lived
+/// structures or be handed to another owner.
+///
+/// # Examples:
+///
+/// ```
+/// use std::any::Any;
+/// use kbehdz::OwnedBindings;
+/// let mut kc: OwnedBindings<String, i32> = OwnedBindings::new();
+/// let n = 1;
+/// kc.bind_fn("one", move || n);
+/// let boxed: Box<dyn Any> = Box::new(kc);
+/// let kc = boxed.downcast::<OwnedBindings<String, i32>>().unwrap();
+/// assert_eq!(kc.run_action("one"), Some(1));
+/// ```
+pub type OwnedBindings<E, R> = ScopedBindings<'static, E, R>;
+
+impl<'a, E, R> Default for ScopedBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    fn default() -> Self {
+        ScopedBindings::new()
+    }
+}
+
+impl<'a, T, E, R> FromIterator<&'a (&'a T, Action<'a, R>)>
+    for ScopedBindings<'a, E, R>
+    where E: Borrow<T> + Hash + Eq,
+          T: ToOwned<Owned=E> + ?Sized + 'a,
+          R: 'a
+{
+    /// Make a new `ScopedBindings` out of an iterator over a
+    /// table of borrowed actions, as with `Bindings`.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, ScopedBindings};
+    /// fn yell() -> String {
+    ///     "yell".to_string()
+    /// }
+    /// const DEFAULTS: &[(&str, Action<String>)] = &[("X", &yell)];
+    /// let name = "screen".to_string();
+    /// let title = || name.clone();
+    /// let mut kc: ScopedBindings<String, String> = DEFAULTS.iter().collect();
+    /// kc.bind_ref("S", &title);
+    /// kc.bind_fn("L", || name.len().to_string());
+    /// assert_eq!(kc.run_action("X").unwrap(), "yell");
+    /// assert_eq!(kc.run_action("S").unwrap(), "screen");
+    /// assert_eq!(kc.run_action("L").unwrap(), "6");
+    /// ```
+    fn from_iter<U>(iter: U) -> Self
+        where U: IntoIterator<Item=&'a (&'a T, Action<'a, R>)>
+    {
+        let mut kc = ScopedBindings::new();
+        for &(event, action) in iter {
+            kc.bind_ref(event, action);
+        }
+        kc
+    }
+}
+
+impl<'a, E, R> ScopedBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Make a new empty binding.
+    pub fn new() -> Self {
+        ScopedBindings(HashMap::new())
+    }
+
+    /// Make a new `ScopedBindings` out of a slice of
+    /// borrowed bindings, as `Bindings` is made by
+    /// `collect()`. The last binding for an event wins.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, ScopedBindings};
+    /// let one: Action<usize> = &|| 1;
+    /// let two: Action<usize> = &|| 2;
+    /// let kc: ScopedBindings<String, usize> =
+    ///     ScopedBindings::with_init(&[("a", one), ("b", two)]);
+    /// assert_eq!(kc.run_action("a"), Some(1));
+    /// assert_eq!(kc.run_action("b"), Some(2));
+    /// ```
+    pub fn with_init<T>(bindings: &[(&T, Action<'a, R>)]) -> Self
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        let mut kc = ScopedBindings::new();
+        for &(event, action) in bindings {
+            kc.bind_ref(event, action);
+        }
+        kc
+    }
+
+    /// Overwrite or create a binding. The event must be
+    /// passed by reference: it will be converted to an
+    /// owned type.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::OwnedBindings;
+    /// let mut kc = OwnedBindings::new();
+    /// let n = 3;
+    /// kc.bind_action(&'a', Box::new(move || n + 1));
+    /// assert_eq!(kc.run_action(&'a').unwrap(), 4);
+    /// ```
+    pub fn bind_action<T>(&mut self, event: &T, action: Box<dyn Fn() -> R + 'a>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.0.insert(event.to_owned(), action);
+    }
+
+    /// Overwrite or create a binding to a closure, boxing
+    /// it here. Unlike `Bindings::bind_fn()`, which takes a
+    /// reference, this takes the closure itself, so it can
+    /// be written inline.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::OwnedBindings;
+    /// let mut kc = OwnedBindings::new();
+    /// kc.bind_fn("quit", || 0);
+    /// let level = 3;
+    /// kc.bind_fn("level", move || level);
+    /// let name = String::from("player");
+    /// kc.bind_fn("name-length", move || name.len());
+    /// assert_eq!(kc.run_action("quit"), Some(0));
+    /// assert_eq!(kc.run_action("level"), Some(3));
+    /// assert_eq!(kc.run_action("name-length"), Some(6));
+    /// ```
+    pub fn bind_fn<T, F>(&mut self, event: &T, f: F)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized, F: Fn() -> R + 'a
+    {
+        self.bind_action(event, Box::new(f));
+    }
+
+    /// Overwrite or create a binding to a function that
+    /// never returns, as made by `diverging()`.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::OwnedBindings;
+    /// fn abort() -> ! {
+    ///     panic!("aborted")
+    /// }
+    /// let mut kc = OwnedBindings::new();
+    /// kc.bind_diverging("C-c", abort);
+    /// kc.bind_fn("h", || "help");
+    /// assert!(kc.get_action("C-c").is_some());
+    /// assert_eq!(kc.run_action("h"), Some("help"));
+    /// ```
+    pub fn bind_diverging<T>(&mut self, event: &T, f: fn() -> !)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.bind_action(event, Box::new(diverging(f)));
+    }
+
+    /// Overwrite or create a binding to a borrowed action,
+    /// such as an entry of a table of functions or a
+    /// closure that outlives the bindings.
+    pub fn bind_ref<T>(&mut self, event: &T, action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.bind_action(event, Box::new(action));
+    }
+
+    /// Given an event that is in the bindings, run the
+    /// corresponding action and return the result.  Return
+    /// `None` if no such event is bound.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.get_action(event).map(|action| action())
+    }
+
+    /// Given an event that is in the bindings, return the
+    /// corresponding action unexecuted.  Return `None` if
+    /// no such event is bound.
+    pub fn get_action<T>(&self, event: &T) -> Option<&dyn Fn() -> R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.0.get(event).map(|action| &**action)
+    }
+
+    /// Build new bindings from these, passing each binding
+    /// through `f`. Bindings for which `f` returns `None`
+    /// are dropped; the rest are rebound as `f` says.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::OwnedBindings;
+    /// let mut kc = OwnedBindings::new();
+    /// for n in 1..5_u32 {
+    ///     kc.bind_action(&n, Box::new(move || n));
+    /// }
+    /// let evens: OwnedBindings<u32, u32> = kc.filter_map_into(|n, action| {
+    ///     if n % 2 == 0 {
+    ///         Some((n, Box::new(move || action() * 2)))
+    ///     } else {
+    ///         None
+    ///     }
+    /// });
+    /// assert!(evens.run_action(&1).is_none());
+    /// assert_eq!(evens.run_action(&2), Some(4));
+    /// assert_eq!(evens.run_action(&4), Some(8));
+    /// ```
+    pub fn filter_map_into<E2, R2, F>(self, f: F) -> ScopedBindings<'a, E2, R2>
+        where E2: Hash + Eq,
+              R2: 'a,
+              F: Fn(E, Box<dyn Fn() -> R + 'a>) -> Option<(E2, Box<dyn Fn() -> R2 + 'a>)>
+    {
+        ScopedBindings(self.0
+                      .into_iter()
+                      .filter_map(|(event, action)| f(event, action))
+                      .collect())
+    }
+}
+
+impl<E, R> Bindings<'static, E, R>
+    where E: Hash + Eq + Clone, R: 'static
+{
+    /// Make an `OwnedBindings` with the same bindings as
+    /// this one. Each action is boxed by capturing its
+    /// reference, which is why the actions must be
+    /// `'static`: static tables of functions, as in the
+    /// `agitate` example, are the usual case.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, Bindings, OwnedBindings};
+    /// fn yell() -> String {
+    ///     "yell".to_string()
+    /// }
+    /// const KEYCODES: &[(&str, Action<String>)] = &[("X", &yell)];
+    /// let kc: Bindings<String, String> = KEYCODES.iter().collect();
+    /// let owned: OwnedBindings<String, String> = kc.to_owned_bindings();
+    /// drop(kc);
+    /// assert_eq!(owned.run_action("X").unwrap(), "yell");
+    /// ```
+    pub fn to_owned_bindings(&self) -> OwnedBindings<E, R> {
+        let mut owned = OwnedBindings::new();
+        for (event, &action) in &self.store {
+            let action: Box<dyn Fn() -> R> = Box::new(action);
+            owned.0.insert(event.clone(), action);
+        }
+        owned
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Glob-pattern bindings for hierarchical string events
+//! such as `menu.file.save`.
+//!
+//! An event is split into segments at a separator. In a
+//! pattern, a segment `*` matches any one segment and a
+//! segment `**` matches any number of segments, including
+//! none. Any other segment, including one merely containing
+//! `*`, matches only itself.
+
+use std::cmp::Reverse;
+
+use {Action, Bindings};
+
+/// A segment of a compiled pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Any,
+    AnyDepth,
+}
+
+/// A compiled pattern.
+#[derive(Debug, Clone)]
+struct Pattern {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl Pattern {
+    fn compile(source: &str, separator: char) -> Self {
+        let segments = source
+            .split(separator)
+            .map(|segment| match segment {
+                "*" => Segment::Any,
+                "**" => Segment::AnyDepth,
+                literal => Segment::Literal(literal.to_string()),
+            })
+            .collect();
+        Pattern {
+            source: source.to_string(),
+            segments,
+        }
+    }
+
+    /// Patterns are more specific with more literal
+    /// segments, then with fewer `**` segments, then with
+    /// fewer `*` segments.
+    fn specificity(&self) -> (usize, Reverse<usize>, Reverse<usize>) {
+        let count = |kind: &Segment| {
+            self.segments.iter().filter(|s| *s == kind).count()
+        };
+        let literals = self.segments.len()
+            - count(&Segment::Any)
+            - count(&Segment::AnyDepth);
+        (literals, Reverse(count(&Segment::AnyDepth)), Reverse(count(&Segment::Any)))
+    }
+
+    fn matches(&self, event: &str, separator: char) -> bool {
+        let event: Vec<&str> = event.split(separator).collect();
+        matches_segments(&self.segments, &event)
+    }
+}
+
+/// True if `pattern` matches all of `event`.
+fn matches_segments(pattern: &[Segment], event: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => event.is_empty(),
+        Some((Segment::AnyDepth, rest)) => {
+            (0..=event.len()).any(|skip| matches_segments(rest, &event[skip..]))
+        },
+        Some((segment, rest)) => match event.split_first() {
+            None => false,
+            Some((first, others)) => {
+                let matched = match *segment {
+                    Segment::Literal(ref literal) => literal == first,
+                    _ => true,
+                };
+                matched && matches_segments(rest, others)
+            },
+        },
+    }
+}
+
+/// A `PatternBindings` object binds string events exactly,
+/// as a `Bindings` does, and also binds glob patterns that
+/// are tried when no exact binding is found. Exact bindings
+/// always win; otherwise the most specific matching pattern
+/// wins, and among equally specific patterns the one bound
+/// first. Patterns are compiled when bound.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::PatternBindings;
+/// let save = || "save";
+/// let edit = || "edit";
+/// let menu = || "menu";
+/// let mut kc = PatternBindings::new('.');
+/// kc.bind_action("menu.file.save", &save);
+/// kc.bind_pattern("menu.edit.*", &edit);
+/// kc.bind_pattern("menu.**", &menu);
+/// assert_eq!(kc.run_action("menu.file.save"), Some("save"));
+/// assert_eq!(kc.run_action("menu.edit.undo"), Some("edit"));
+/// assert_eq!(kc.run_action("menu.edit.undo.all"), Some("menu"));
+/// assert_eq!(kc.run_action("menu"), Some("menu"));
+/// assert!(kc.run_action("file.save").is_none());
+/// ```
+pub struct PatternBindings<'a, R: 'a> {
+    bindings: Bindings<'a, String, R>,
+    patterns: Vec<(Pattern, Action<'a, R>)>,
+    separator: char,
+}
+
+impl<'a, R> Default for PatternBindings<'a, R> {
+    fn default() -> Self {
+        PatternBindings::new('.')
+    }
+}
+
+impl<'a, R> PatternBindings<'a, R> {
+    /// Make a new empty binding, splitting events into
+    /// segments at `separator`.
+    pub fn new(separator: char) -> Self {
+        PatternBindings {
+            bindings: Bindings::new(),
+            patterns: Vec::new(),
+            separator,
+        }
+    }
+
+    /// The exact bindings.
+    pub fn bindings(&self) -> &Bindings<'a, String, R> {
+        &self.bindings
+    }
+
+    /// Overwrite or create an exact binding.
+    pub fn bind_action(&mut self, event: &str, action: Action<'a, R>) {
+        self.bindings.bind_action(event, action);
+    }
+
+    /// Remove the exact binding for `event`, returning its
+    /// action.
+    pub fn unbind_action(&mut self, event: &str) -> Option<Action<'a, R>> {
+        self.bindings.unbind_action(event)
+    }
+
+    /// Overwrite or create a pattern binding.
+    pub fn bind_pattern(&mut self, pattern: &str, action: Action<'a, R>) {
+        if let Some(entry) = self.patterns.iter_mut().find(|(p, _)| p.source == pattern) {
+            entry.1 = action;
+            return;
+        }
+        let pattern = Pattern::compile(pattern, self.separator);
+        let specificity = pattern.specificity();
+        // Keep the patterns most specific first, and in
+        // binding order among equals.
+        let index = self.patterns
+            .iter()
+            .position(|(p, _)| p.specificity() < specificity)
+            .unwrap_or(self.patterns.len());
+        self.patterns.insert(index, (pattern, action));
+    }
+
+    /// Remove the binding for `pattern`, returning its
+    /// action.
+    pub fn unbind_pattern(&mut self, pattern: &str) -> Option<Action<'a, R>> {
+        let index = self.patterns.iter().position(|(p, _)| p.source == pattern)?;
+        Some(self.patterns.remove(index).1)
+    }
+
+    /// Given an event that is bound exactly or matched by a
+    /// pattern, run the corresponding action and return the
+    /// result.  Return `None` if there is no such action.
+    pub fn run_action(&self, event: &str) -> Option<R> {
+        self.get_action(event).map(|action| action())
+    }
+
+    /// Given an event that is bound exactly or matched by a
+    /// pattern, return the corresponding action unexecuted.
+    /// Return `None` if there is no such action.
+    pub fn get_action(&self, event: &str) -> Option<Action<'a, R>> {
+        self.bindings.get_action(event).or_else(|| {
+            self.patterns
+                .iter()
+                .find(|(pattern, _)| pattern.matches(event, self.separator))
+                .map(|&(_, action)| action)
+        })
+    }
+
+    /// The exactly bound events matched by `pattern`, in
+    /// sorted order. This is for debugging patterns.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::PatternBindings;
+    /// let act = || ();
+    /// let mut kc = PatternBindings::new('/');
+    /// kc.bind_action("menu/file/save", &act);
+    /// kc.bind_action("menu/file/open", &act);
+    /// kc.bind_action("menu/edit/undo", &act);
+    /// assert_eq!(kc.events_matching("menu/file/*"),
+    ///            vec!["menu/file/open", "menu/file/save"]);
+    /// assert_eq!(kc.events_matching("**/undo"), vec!["menu/edit/undo"]);
+    /// ```
+    pub fn events_matching(&self, pattern: &str) -> Vec<&String> {
+        let pattern = Pattern::compile(pattern, self.separator);
+        let mut events: Vec<&String> = self.bindings.store
+            .keys()
+            .filter(|event| pattern.matches(event, self.separator))
+            .collect();
+        events.sort();
+        events
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Bindings that are never changed in place, for cheap
+//! "what if" copies such as previews of proposed changes.
+//!
+//! The bindings are spread over a fixed number of buckets,
+//! each shared by reference count. A changed copy shares
+//! every bucket but the one holding the changed event, so
+//! making it costs one bucket copy plus a pointer copy per
+//! bucket, rather than a copy of the whole map.
+
+use std::borrow::{Borrow, ToOwned};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use {Action, Bindings};
+
+/// Number of buckets.
+const BUCKETS: usize = 64;
+
+/// Type of a bucket.
+type Bucket<'a, E, R> = Rc<Vec<(E, Action<'a, R>)>>;
+
+/// A `PersistentBindings` object is an immutable binding
+/// of events to actions. Changing it makes a new object
+/// sharing most of its storage with the old one, which is
+/// left as it was.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::PersistentBindings;
+/// let fire = || "fire";
+/// let jump = || "jump";
+/// let committed = PersistentBindings::new().with_binding("f", &fire);
+/// let preview = committed.with_binding("j", &jump).without_binding("f");
+/// assert_eq!(preview.run_action("j"), Some("jump"));
+/// assert!(preview.run_action("f").is_none());
+/// assert_eq!(committed.run_action("f"), Some("fire"));
+/// assert!(committed.run_action("j").is_none());
+/// ```
+pub struct PersistentBindings<'a, E, R: 'a> {
+    buckets: Rc<Vec<Bucket<'a, E, R>>>,
+    len: usize,
+}
+
+impl<'a, E, R> Clone for PersistentBindings<'a, E, R> {
+    fn clone(&self) -> Self {
+        PersistentBindings {
+            buckets: Rc::clone(&self.buckets),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, E, R> Default for PersistentBindings<'a, E, R>
+    where E: Hash + Eq + Clone
+{
+    fn default() -> Self {
+        PersistentBindings::new()
+    }
+}
+
+/// Index of the bucket for `event`.
+fn bucket_of<T: Hash + ?Sized>(event: &T) -> usize {
+    let mut hasher = DefaultHasher::new();
+    event.hash(&mut hasher);
+    (hasher.finish() % BUCKETS as u64) as usize
+}
+
+impl<'a, E, R> PersistentBindings<'a, E, R>
+    where E: Hash + Eq + Clone
+{
+    /// Make a new empty binding.
+    pub fn new() -> Self {
+        let empty = Rc::new(Vec::new());
+        PersistentBindings {
+            buckets: Rc::new(vec![empty; BUCKETS]),
+            len: 0,
+        }
+    }
+
+    /// Return a copy of these bindings with `event` bound
+    /// to `action`.
+    pub fn with_binding<T>(&self, event: &T, action: Action<'a, R>) -> Self
+        where E: Borrow<T>, T: ToOwned<Owned=E> + Hash + Eq + ?Sized
+    {
+        let index = bucket_of(event);
+        let mut bucket: Vec<(E, Action<'a, R>)> = (*self.buckets[index]).clone();
+        let mut len = self.len;
+        match bucket.iter_mut().find(|(e, _)| e.borrow() == event) {
+            Some(entry) => entry.1 = action,
+            None => {
+                bucket.push((event.to_owned(), action));
+                len += 1;
+            },
+        }
+        self.replacing(index, bucket, len)
+    }
+
+    /// Return a copy of these bindings with `event`
+    /// unbound.
+    pub fn without_binding<T>(&self, event: &T) -> Self
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        let index = bucket_of(event);
+        if !self.buckets[index].iter().any(|(e, _)| e.borrow() == event) {
+            return self.clone();
+        }
+        let bucket = self.buckets[index]
+            .iter()
+            .filter(|(e, _)| e.borrow() != event)
+            .cloned()
+            .collect();
+        self.replacing(index, bucket, self.len - 1)
+    }
+
+    /// Copy of these bindings with bucket `index` replaced.
+    fn replacing(&self, index: usize, bucket: Vec<(E, Action<'a, R>)>, len: usize) -> Self {
+        let mut buckets = (*self.buckets).clone();
+        buckets[index] = Rc::new(bucket);
+        PersistentBindings {
+            buckets: Rc::new(buckets),
+            len,
+        }
+    }
+
+    /// Given an event that is in the bindings, run the
+    /// corresponding action and return the result.  Return
+    /// `None` if no such event is bound.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.get_action(event).map(|action| action())
+    }
+
+    /// Given an event that is in the bindings, return the
+    /// corresponding action unexecuted.  Return `None` if
+    /// no such event is bound.
+    pub fn get_action<T>(&self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.buckets[bucket_of(event)]
+            .iter()
+            .find(|(e, _)| e.borrow() == event)
+            .map(|&(_, action)| action)
+    }
+
+    /// Number of bound events.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// True if no events are bound.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Make a `Bindings` with the same bindings.
+    pub fn to_bindings(&self) -> Bindings<'a, E, R> {
+        let mut kc = Bindings::new();
+        for &(ref event, action) in self.buckets.iter().flat_map(|bucket| bucket.iter()) {
+            kc.store.insert(event.clone(), action);
+        }
+        kc
+    }
+}
+
+impl<'a, E, R> Bindings<'a, E, R>
+    where E: Hash + Eq + Clone, R: 'a
+{
+    /// Make a `PersistentBindings` with the same bindings.
+    pub fn to_persistent(&self) -> PersistentBindings<'a, E, R> {
+        let mut buckets: Vec<Vec<(E, Action<'a, R>)>> = vec![Vec::new(); BUCKETS];
+        for (event, &action) in &self.store {
+            buckets[bucket_of(event)].push((event.clone(), action));
+        }
+        PersistentBindings {
+            buckets: Rc::new(buckets.into_iter().map(Rc::new).collect()),
+            len: self.store.len(),
+        }
+    }
+}
@@ -0,0 +1,269 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Bindings contributed by several sources (engine
+//! defaults, plugins, user configuration) with explicit
+//! priorities instead of "last bind wins".
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::borrow::{Borrow, ToOwned};
+
+use Action;
+
+/// Identifies who added a binding, so that bindings can be
+/// removed by source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SourceId(pub u32);
+
+/// One of possibly several bindings for an event.
+pub struct PriorityEntry<'a, R: 'a> {
+    /// The bound action.
+    pub action: Action<'a, R>,
+    /// Higher priorities win.
+    pub priority: i32,
+    /// Who added the binding.
+    pub source: SourceId,
+}
+
+impl<'a, R> Clone for PriorityEntry<'a, R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, R> Copy for PriorityEntry<'a, R> {}
+
+/// A `PriorityBindings` object keeps, for each event, every
+/// binding made for it, and dispatches to the one with the
+/// highest priority. Ties go to the most recent binding.
+/// Each source has at most one binding per event:
+/// rebinding from the same source replaces it.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{Action, PriorityBindings, SourceId};
+/// const ENGINE: SourceId = SourceId(0);
+/// const USER: SourceId = SourceId(1);
+/// let walk: Action<&str> = &|| "walk";
+/// let run: Action<&str> = &|| "run";
+/// let mut kc: PriorityBindings<String, &str> = PriorityBindings::new();
+/// kc.bind_action_with_priority("w", run, 10, USER);
+/// kc.bind_action_with_priority("w", walk, 0, ENGINE);
+/// assert_eq!(kc.run_action("w"), Some("run"));
+/// ```
+pub struct PriorityBindings<'a, E, R>(HashMap<E, Vec<PriorityEntry<'a, R>>>)
+    where E: Hash + Eq, R: 'a;
+
+impl<'a, E, R> Default for PriorityBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    fn default() -> Self {
+        PriorityBindings::new()
+    }
+}
+
+impl<'a, E, R> PriorityBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Make a new empty binding.
+    pub fn new() -> Self {
+        PriorityBindings(HashMap::new())
+    }
+
+    /// Add a binding from `source` for `event` at the given
+    /// `priority`, replacing any binding `source` already
+    /// has for `event`.
+    pub fn bind_action_with_priority<T>(&mut self,
+                                        event: &T,
+                                        action: Action<'a, R>,
+                                        priority: i32,
+                                        source: SourceId)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        let entries = self.0.entry(event.to_owned()).or_default();
+        entries.retain(|entry| entry.source != source);
+        let posn = entries
+            .iter()
+            .position(|entry| entry.priority <= priority)
+            .unwrap_or(entries.len());
+        entries.insert(posn, PriorityEntry { action, priority, source });
+    }
+
+    /// Add a binding from `source` for `event` at priority
+    /// zero. A later binding shadows an earlier one from
+    /// another source until it is removed.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, PriorityBindings, SourceId};
+    /// const BUILTIN: SourceId = SourceId(0);
+    /// const PLUGIN: SourceId = SourceId(7);
+    /// let help: Action<&str> = &|| "help";
+    /// let wiki: Action<&str> = &|| "wiki";
+    /// let mut kc: PriorityBindings<String, &str> = PriorityBindings::new();
+    /// kc.bind_action_from(BUILTIN, "F1", help);
+    /// kc.bind_action_from(PLUGIN, "F1", wiki);
+    /// kc.bind_action_from(PLUGIN, "F2", wiki);
+    /// assert_eq!(kc.source_of("F1"), Some(PLUGIN));
+    /// assert_eq!(kc.run_action("F1"), Some("wiki"));
+    /// assert_eq!(kc.unbind_source(PLUGIN), 2);
+    /// assert_eq!(kc.run_action("F1"), Some("help"));
+    /// assert!(kc.run_action("F2").is_none());
+    /// assert_eq!(kc.sources(), vec![BUILTIN]);
+    /// ```
+    pub fn bind_action_from<T>(&mut self,
+                               source: SourceId,
+                               event: &T,
+                               action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.bind_action_with_priority(event, action, 0, source);
+    }
+
+    /// Remove every binding `source` made, revealing any
+    /// bindings they shadowed. Return the number of
+    /// bindings removed.
+    pub fn unbind_source(&mut self, source: SourceId) -> usize {
+        let mut removed = 0;
+        for entries in self.0.values_mut() {
+            let before = entries.len();
+            entries.retain(|entry| entry.source != source);
+            removed += before - entries.len();
+        }
+        self.0.retain(|_, entries| !entries.is_empty());
+        removed
+    }
+
+    /// Return the sources that currently have bindings, in
+    /// increasing order.
+    pub fn sources(&self) -> Vec<SourceId> {
+        let mut sources: Vec<SourceId> = self.0
+            .values()
+            .flat_map(|entries| entries.iter().map(|entry| entry.source))
+            .collect();
+        sources.sort();
+        sources.dedup();
+        sources
+    }
+
+    /// Return the source of the winning binding for
+    /// `event`, or `None` if no such event is bound.
+    pub fn source_of<T>(&self, event: &T) -> Option<SourceId>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.0.get(event).map(|entries| entries[0].source)
+    }
+
+    /// Given an event that is in the bindings, run the
+    /// winning action and return the result. Return `None`
+    /// if no such event is bound.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.get_action(event).map(|action| action())
+    }
+
+    /// Given an event that is in the bindings, return the
+    /// winning action unexecuted. Return `None` if no such
+    /// event is bound.
+    pub fn get_action<T>(&self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.0.get(event).map(|entries| entries[0].action)
+    }
+
+    /// Return every binding for `event`, winner first.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, PriorityBindings, SourceId};
+    /// let walk: Action<&str> = &|| "walk";
+    /// let run: Action<&str> = &|| "run";
+    /// let mut kc: PriorityBindings<char, &str> = PriorityBindings::new();
+    /// kc.bind_action_with_priority(&'w', walk, 5, SourceId(0));
+    /// kc.bind_action_with_priority(&'w', run, 5, SourceId(1));
+    /// let sources: Vec<_> = kc.bindings_for_event(&'w')
+    ///     .iter()
+    ///     .map(|entry| entry.source)
+    ///     .collect();
+    /// assert_eq!(sources, vec![SourceId(1), SourceId(0)]);
+    /// ```
+    pub fn bindings_for_event<T>(&self, event: &T) -> Vec<PriorityEntry<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.0.get(event).cloned().unwrap_or_default()
+    }
+
+    /// Remove the winning binding for `event`, revealing
+    /// the next one if any. Return the removed binding.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, PriorityBindings, SourceId};
+    /// let walk: Action<&str> = &|| "walk";
+    /// let run: Action<&str> = &|| "run";
+    /// let mut kc: PriorityBindings<char, &str> = PriorityBindings::new();
+    /// kc.bind_action_with_priority(&'w', walk, 0, SourceId(0));
+    /// kc.bind_action_with_priority(&'w', run, 1, SourceId(1));
+    /// assert_eq!(kc.unbind_top(&'w').unwrap().source, SourceId(1));
+    /// assert_eq!(kc.run_action(&'w'), Some("walk"));
+    /// assert!(kc.unbind_top(&'w').is_some());
+    /// assert!(kc.run_action(&'w').is_none());
+    /// ```
+    pub fn unbind_top<T>(&mut self, event: &T) -> Option<PriorityEntry<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.unbind_where(event, |_| true)
+    }
+
+    /// Remove the binding `source` made for `event`,
+    /// leaving other sources' bindings alone. Return the
+    /// removed binding.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, PriorityBindings, SourceId};
+    /// let walk: Action<&str> = &|| "walk";
+    /// let run: Action<&str> = &|| "run";
+    /// let mut kc: PriorityBindings<char, &str> = PriorityBindings::new();
+    /// kc.bind_action_with_priority(&'w', walk, 0, SourceId(0));
+    /// kc.bind_action_with_priority(&'w', run, 1, SourceId(1));
+    /// assert!(kc.unbind_from(&'w', SourceId(0)).is_some());
+    /// assert!(kc.unbind_from(&'w', SourceId(0)).is_none());
+    /// assert_eq!(kc.run_action(&'w'), Some("run"));
+    /// ```
+    pub fn unbind_from<T>(&mut self, event: &T, source: SourceId)
+                          -> Option<PriorityEntry<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.unbind_where(event, |entry| entry.source == source)
+    }
+
+    /// Remove the first binding for `event` satisfying
+    /// `pred`, dropping the event once it has no bindings.
+    fn unbind_where<T, P>(&mut self, event: &T, pred: P)
+                          -> Option<PriorityEntry<'a, R>>
+        where E: Borrow<T>,
+              T: Hash + Eq + ?Sized,
+              P: Fn(&PriorityEntry<'a, R>) -> bool
+    {
+        let (removed, now_empty) = {
+            let entries = self.0.get_mut(event)?;
+            let posn = entries.iter().position(pred)?;
+            let removed = entries.remove(posn);
+            (removed, entries.is_empty())
+        };
+        if now_empty {
+            self.0.remove(event);
+        }
+        Some(removed)
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Actions that run external programs, for hotkey-daemon
+//! style tools. Enabled by the `process` feature.
+//!
+//! Command lines are split into words without a shell:
+//! words are separated by whitespace; single quotes
+//! preserve everything up to the closing quote; double
+//! quotes preserve everything but allow `\"` and `\\`
+//! escapes; outside quotes a backslash escapes the next
+//! character. Any word containing `$KBEHDZ_EVENT` has it
+//! replaced by the triggering event, which is also passed
+//! to the program in the `KBEHDZ_EVENT` environment
+//! variable.
+
+use std::borrow::{Borrow, ToOwned};
+use std::fmt::Display;
+use std::hash::Hash;
+use std::io;
+use std::process::{Child, Command, ExitStatus};
+
+use ScopedBindings;
+
+/// Name of the variable holding the triggering event.
+const EVENT_VAR: &str = "KBEHDZ_EVENT";
+
+/// Split `cmdline` into words as described in the module
+/// documentation. Fail with `InvalidInput` on an
+/// unterminated quote or escape, or an empty command.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::process::split_command;
+/// let words = split_command(r#"notify-send "Hi, \"you\"" '$x' a\ b"#);
+/// assert_eq!(words.unwrap(),
+///            vec!["notify-send", "Hi, \"you\"", "$x", "a b"]);
+/// assert!(split_command("echo \"oops").is_err());
+/// assert!(split_command("   ").is_err());
+/// ```
+pub fn split_command(cmdline: &str) -> io::Result<Vec<String>> {
+    let bad = |msg: &str| {
+        io::Error::new(io::ErrorKind::InvalidInput,
+                       format!("{}: {}", msg, cmdline))
+    };
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = cmdline.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(w) = word.take() {
+                    words.push(w);
+                }
+            },
+            '\'' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => w.push(c),
+                        None => return Err(bad("unterminated quote")),
+                    }
+                }
+            },
+            '"' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) if c == '"' || c == '\\' => w.push(c),
+                            Some(c) => {
+                                w.push('\\');
+                                w.push(c);
+                            },
+                            None => return Err(bad("unterminated quote")),
+                        },
+                        Some(c) => w.push(c),
+                        None => return Err(bad("unterminated quote")),
+                    }
+                }
+            },
+            '\\' => match chars.next() {
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => return Err(bad("unterminated escape")),
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(w) = word {
+        words.push(w);
+    }
+    if words.is_empty() {
+        return Err(bad("empty command"));
+    }
+    Ok(words)
+}
+
+/// Build the command for `words`, expanding the event if
+/// there is one.
+fn command(words: &io::Result<Vec<String>>, event: Option<&str>)
+           -> io::Result<Command>
+{
+    let words = match *words {
+        Ok(ref words) => words,
+        Err(ref e) => return Err(io::Error::new(e.kind(), e.to_string())),
+    };
+    let pattern = format!("${}", EVENT_VAR);
+    let expand = |word: &String| match event {
+        Some(event) => word.replace(&pattern, event),
+        None => word.clone(),
+    };
+    let mut cmd = Command::new(expand(&words[0]));
+    cmd.args(words[1..].iter().map(expand));
+    if let Some(event) = event {
+        cmd.env(EVENT_VAR, event);
+    }
+    Ok(cmd)
+}
+
+/// Make an action that runs `cmd` and waits for it to
+/// finish. The command line is split once, here; a bad
+/// command line makes every run of the action fail.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::process::shell_action;
+/// if cfg!(unix) {
+///     let status = shell_action("sh -c 'exit 3'")().unwrap();
+///     assert_eq!(status.code(), Some(3));
+/// }
+/// let bad = shell_action("'");
+/// assert!(bad().is_err());
+/// ```
+pub fn shell_action<S>(cmd: S) -> impl Fn() -> io::Result<ExitStatus>
+    where S: Into<String>
+{
+    let words = split_command(&cmd.into());
+    move || command(&words, None)?.status()
+}
+
+/// Make an action that starts `cmd` without waiting for
+/// it, returning the running `Child`. Use this for
+/// long-running programs.
+pub fn spawn_action<S>(cmd: S) -> impl Fn() -> io::Result<Child>
+    where S: Into<String>
+{
+    let words = split_command(&cmd.into());
+    move || command(&words, None)?.spawn()
+}
+
+impl<'a, E> ScopedBindings<'a, E, io::Result<ExitStatus>>
+    where E: Hash + Eq + Display
+{
+    /// Bind `event` to run `cmdline` and wait for it, with
+    /// `$KBEHDZ_EVENT` expanded to the event.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::OwnedBindings;
+    /// let mut kc = OwnedBindings::new();
+    /// kc.bind_shell("F5", r#"sh -c 'test "$1" = F5' - $KBEHDZ_EVENT"#);
+    /// if cfg!(unix) {
+    ///     assert!(kc.run_action("F5").unwrap().unwrap().success());
+    /// }
+    /// ```
+    pub fn bind_shell<T, S>(&mut self, event: &T, cmdline: S)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized, S: Into<String>
+    {
+        let name = event.to_owned().to_string();
+        let words = split_command(&cmdline.into());
+        self.bind_action(event, Box::new(move || {
+            command(&words, Some(&name))?.status()
+        }));
+    }
+}
+
+impl<'a, E> ScopedBindings<'a, E, io::Result<Child>>
+    where E: Hash + Eq + Display
+{
+    /// Bind `event` to start `cmdline` without waiting for
+    /// it, with `$KBEHDZ_EVENT` expanded to the event.
+    pub fn bind_spawn<T, S>(&mut self, event: &T, cmdline: S)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized, S: Into<String>
+    {
+        let name = event.to_owned().to_string();
+        let words = split_command(&cmdline.into());
+        self.bind_action(event, Box::new(move || {
+            command(&words, Some(&name))?.spawn()
+        }));
+    }
+}
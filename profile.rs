@@ -0,0 +1,233 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Timing of actions, for finding slow commands.
+
+use std::borrow::Borrow;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use {Bindings, Source};
+
+/// Accumulated run times of an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    /// Number of runs.
+    pub count: u32,
+    /// Total time of all runs.
+    pub total: Duration,
+    /// Time of the fastest run.
+    pub min: Duration,
+    /// Time of the slowest run.
+    pub max: Duration,
+}
+
+impl Timing {
+    /// Timing of a single run.
+    fn new(elapsed: Duration) -> Self {
+        Timing {
+            count: 1,
+            total: elapsed,
+            min: elapsed,
+            max: elapsed,
+        }
+    }
+
+    /// Add another run.
+    fn record(&mut self, elapsed: Duration) {
+        self.count += 1;
+        self.total += elapsed;
+        self.min = self.min.min(elapsed);
+        self.max = self.max.max(elapsed);
+    }
+
+    /// Average time of a run.
+    pub fn mean(&self) -> Duration {
+        self.total / self.count
+    }
+}
+
+/// A `ProfiledBindings` object wraps a `Bindings`, timing
+/// each action it runs.
+///
+/// # Examples:
+///
+/// ```
+/// use std::thread::sleep;
+/// use std::time::Duration;
+/// use kbehdz::{Bindings, ProfiledBindings};
+/// let nap = || sleep(Duration::from_millis(2));
+/// let mut kc = Bindings::new();
+/// kc.bind_action("z", &nap);
+/// let kc = ProfiledBindings::new(kc);
+/// assert!(kc.timing("z").is_none());
+/// kc.run_action("z");
+/// kc.run_action("z");
+/// assert!(kc.run_action("q").is_none());
+/// let timing = kc.timing("z").unwrap();
+/// assert_eq!(timing.count, 2);
+/// assert!(timing.min >= Duration::from_millis(2));
+/// assert!(timing.min <= timing.max);
+/// assert!(timing.total >= Duration::from_millis(4));
+/// assert!(kc.timing("q").is_none());
+/// ```
+pub struct ProfiledBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    bindings: Bindings<'a, E, R>,
+    timings: RefCell<HashMap<E, Timing>>,
+    fallback_timing: Cell<Option<Timing>>,
+}
+
+impl<'a, E, R> ProfiledBindings<'a, E, R>
+    where E: Hash + Eq + Clone, R: 'a
+{
+    /// Start profiling `bindings`.
+    pub fn new(bindings: Bindings<'a, E, R>) -> Self {
+        ProfiledBindings {
+            bindings,
+            timings: RefCell::new(HashMap::new()),
+            fallback_timing: Cell::new(None),
+        }
+    }
+
+    /// The profiled bindings.
+    pub fn bindings(&self) -> &Bindings<'a, E, R> {
+        &self.bindings
+    }
+
+    /// The profiled bindings, for changing them. Timings
+    /// are kept per event, so they survive rebinding.
+    pub fn bindings_mut(&mut self) -> &mut Bindings<'a, E, R> {
+        &mut self.bindings
+    }
+
+    /// Stop profiling, returning the bindings.
+    pub fn into_inner(self) -> Bindings<'a, E, R> {
+        self.bindings
+    }
+
+    /// Run the action for `event` as `Bindings::run_action()`
+    /// does, following aliases and falling back to the
+    /// fallback action, record how long it took, and return
+    /// the result. Return `None`, recording nothing, if no
+    /// action ran.
+    ///
+    /// Runs of bound or aliased events are recorded under
+    /// the event. Runs of the fallback are all recorded in
+    /// the one `fallback_timing()`, so that unbounded input,
+    /// such as typed text, cannot grow the timings without
+    /// limit.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Bindings, ProfiledBindings};
+    /// let fire = || "fire";
+    /// let beep = || "beep";
+    /// let mut kc: Bindings<String, &str> = Bindings::new();
+    /// kc.bind_action("f", &fire);
+    /// kc.bind_alias("F", "f").unwrap();
+    /// kc.set_fallback(&beep);
+    /// let kc = ProfiledBindings::new(kc);
+    /// assert_eq!(kc.run_action("F"), Some("fire"));
+    /// assert_eq!(kc.run_action("x"), Some("beep"));
+    /// assert_eq!(kc.run_action("y"), Some("beep"));
+    /// assert_eq!(kc.timing("F").unwrap().count, 1);
+    /// assert!(kc.timing("f").is_none());
+    /// assert!(kc.timing("x").is_none());
+    /// assert_eq!(kc.fallback_timing().unwrap().count, 2);
+    /// ```
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        let start = Instant::now();
+        let (result, source) = self.bindings.run_action_reporting(event)?;
+        let elapsed = start.elapsed();
+        if source == Source::Fallback {
+            let timing = match self.fallback_timing.get() {
+                Some(mut timing) => {
+                    timing.record(elapsed);
+                    timing
+                },
+                None => Timing::new(elapsed),
+            };
+            self.fallback_timing.set(Some(timing));
+            return Some(result);
+        }
+        let mut timings = self.timings.borrow_mut();
+        match timings.get_mut(event) {
+            Some(timing) => timing.record(elapsed),
+            None => {
+                let key = match self.bindings.store.get_key_value(event) {
+                    Some((key, _)) => key,
+                    None => self.bindings.aliases.get_key_value(event)?.0,
+                };
+                timings.insert(key.clone(), Timing::new(elapsed));
+            },
+        }
+        Some(result)
+    }
+
+    /// As `run_action()`, but return `None` without running
+    /// the action if its recorded mean time is over
+    /// `budget`. An action that has never run is run. Since
+    /// a skipped action is not timed, it stays skipped
+    /// under that budget until `clear_timings()`.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::thread::sleep;
+    /// use std::time::Duration;
+    /// use kbehdz::{Bindings, ProfiledBindings};
+    /// let slow = || sleep(Duration::from_millis(5));
+    /// let fast = || ();
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action("slow", &slow);
+    /// kc.bind_action("fast", &fast);
+    /// let kc = ProfiledBindings::new(kc);
+    /// let tight = Duration::from_millis(1);
+    /// assert!(kc.run_within("slow", tight).is_some());
+    /// assert!(kc.run_within("slow", tight).is_none());
+    /// assert!(kc.run_within("slow", Duration::from_secs(1)).is_some());
+    /// assert_eq!(kc.timing("slow").unwrap().count, 2);
+    /// assert!(kc.run_within("fast", tight).is_some());
+    /// assert!(kc.run_within("fast", tight).is_some());
+    /// ```
+    pub fn run_within<T>(&self, event: &T, budget: Duration) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        if let Some(timing) = self.timing(event) {
+            if timing.mean() > budget {
+                return None;
+            }
+        }
+        self.run_action(event)
+    }
+
+    /// Return the recorded timing for `event`, or `None`
+    /// if it has never been run.
+    pub fn timing<T>(&self, event: &T) -> Option<Timing>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.timings.borrow().get(event).copied()
+    }
+
+    /// Return the recorded timing of the fallback action,
+    /// over all the events it handled, or `None` if it has
+    /// never been run.
+    pub fn fallback_timing(&self) -> Option<Timing> {
+        self.fallback_timing.get()
+    }
+
+    /// Forget all recorded timings.
+    pub fn clear_timings(&mut self) {
+        self.timings.get_mut().clear();
+        self.fallback_timing.set(None);
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Interactive rebinding: capture the next event as the
+//! new binding for an action, as in a game settings
+//! screen. This is pure logic, so any frontend can drive
+//! it.
+
+use std::hash::Hash;
+
+use {same_action, Action, Bindings};
+
+/// Result of feeding an event to a `RebindCapture`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureOutcome<E> {
+    /// No capture is in progress: the event was ignored.
+    Idle,
+    /// The event is now bound to the target action.
+    Captured(E),
+    /// The cancel event was seen: nothing was changed.
+    Cancelled,
+    /// The event is already bound to some other action.
+    /// Use `RebindCapture::confirm_steal()` to take it
+    /// anyway, or `RebindCapture::cancel()` to give up.
+    Conflict {
+        /// The event that is bound elsewhere.
+        existing_event: E,
+    },
+}
+
+/// State machine for "press a key to rebind" flows.
+///
+/// Capturing an event for an action moves the action: any
+/// events previously bound to it are unbound, so the
+/// action ends up bound only to the captured event.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{Action, Bindings, CaptureOutcome, RebindCapture};
+/// let jump: Action<&str> = &|| "jump";
+/// let fire: Action<&str> = &|| "fire";
+/// let mut kc: Bindings<String, &str> = Bindings::new();
+/// kc.bind_action("space", jump);
+/// kc.bind_action("f", fire);
+///
+/// let mut capture = RebindCapture::new("escape".to_string());
+/// capture.start(jump);
+/// assert_eq!(capture.feed(&mut kc, "j".to_string()),
+///            CaptureOutcome::Captured("j".to_string()));
+/// assert_eq!(kc.run_action("j"), Some("jump"));
+/// assert!(kc.run_action("space").is_none());
+///
+/// capture.start(jump);
+/// assert_eq!(capture.feed(&mut kc, "escape".to_string()),
+///            CaptureOutcome::Cancelled);
+/// assert_eq!(kc.run_action("j"), Some("jump"));
+/// ```
+pub struct RebindCapture<'a, E, R: 'a> {
+    cancel: E,
+    target: Option<Action<'a, R>>,
+    pending: Option<E>,
+}
+
+impl<'a, E, R> RebindCapture<'a, E, R>
+    where E: Hash + Eq + Clone, R: 'a
+{
+    /// Make a new idle capture. Feeding the `cancel` event
+    /// during a capture abandons it.
+    pub fn new(cancel: E) -> Self {
+        RebindCapture {
+            cancel,
+            target: None,
+            pending: None,
+        }
+    }
+
+    /// Begin capturing a new event for the `target`
+    /// action, abandoning any capture in progress.
+    pub fn start(&mut self, target: Action<'a, R>) {
+        self.target = Some(target);
+        self.pending = None;
+    }
+
+    /// True if a capture is in progress, including one
+    /// waiting on a conflict decision.
+    pub fn is_capturing(&self) -> bool {
+        self.target.is_some()
+    }
+
+    /// Abandon any capture in progress.
+    pub fn cancel(&mut self) {
+        self.target = None;
+        self.pending = None;
+    }
+
+    /// Offer the next event to the capture. A free event
+    /// (or one already bound to the target) is bound
+    /// immediately. An event bound to some other action is
+    /// reported as a conflict and left alone until
+    /// `confirm_steal()`. Feeding another event while a
+    /// conflict is pending replaces the pending choice.
+    pub fn feed(&mut self, bindings: &mut Bindings<'a, E, R>, event: E)
+                -> CaptureOutcome<E>
+    {
+        let target = match self.target {
+            Some(target) => target,
+            None => return CaptureOutcome::Idle,
+        };
+        if event == self.cancel {
+            self.cancel();
+            return CaptureOutcome::Cancelled;
+        }
+        if let Some(existing) = bindings.get_action(&event) {
+            if !same_action(existing, target) {
+                self.pending = Some(event.clone());
+                return CaptureOutcome::Conflict { existing_event: event };
+            }
+        }
+        self.cancel();
+        rebind(bindings, target, event.clone());
+        CaptureOutcome::Captured(event)
+    }
+
+    /// Resolve a pending conflict by binding the conflicting
+    /// event to the target, replacing its old action, and
+    /// unbinding the target's previous events. Return the
+    /// captured event, or `None` if no conflict was pending.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, Bindings, CaptureOutcome, RebindCapture};
+    /// let jump: Action<&str> = &|| "jump";
+    /// let fire: Action<&str> = &|| "fire";
+    /// let mut kc: Bindings<char, &str> = Bindings::new();
+    /// kc.bind_action(&' ', jump);
+    /// kc.bind_action(&'f', fire);
+    ///
+    /// let mut capture = RebindCapture::new('\x1b');
+    /// capture.start(jump);
+    /// assert_eq!(capture.feed(&mut kc, 'f'),
+    ///            CaptureOutcome::Conflict { existing_event: 'f' });
+    /// assert_eq!(kc.run_action(&'f'), Some("fire"));
+    /// assert_eq!(capture.confirm_steal(&mut kc), Some('f'));
+    /// assert_eq!(kc.run_action(&'f'), Some("jump"));
+    /// assert!(kc.run_action(&' ').is_none());
+    /// assert!(!capture.is_capturing());
+    /// ```
+    pub fn confirm_steal(&mut self, bindings: &mut Bindings<'a, E, R>)
+                         -> Option<E>
+    {
+        let event = self.pending.take()?;
+        let target = self.target.take()?;
+        rebind(bindings, target, event.clone());
+        Some(event)
+    }
+}
+
+/// Make `event` the only event bound to `target`.
+fn rebind<'a, E, R>(bindings: &mut Bindings<'a, E, R>,
+                    target: Action<'a, R>,
+                    event: E)
+    where E: Hash + Eq, R: 'a
+{
+    bindings.store.retain(|_, &mut action| !same_action(action, target));
+    bindings.insert(event, target);
+}
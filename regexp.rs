@@ -0,0 +1,161 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Regex-matched bindings for textual events, such as chat
+//! or debug-console commands, with actions that receive the
+//! regex captures. Requires the `regex` feature.
+//!
+//! Each regex is matched at most once per dispatch, at its
+//! leftmost match, so a regex that can match the empty
+//! string, such as `x*`, simply matches every event.
+//! Anchor a regex with `^` and `$` to match whole events.
+
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+use regex::{self, Captures, Regex};
+
+use {Action, Bindings};
+
+/// Identifies a regex binding of a `RegexBindings`, for
+/// unbinding it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegexId(u64);
+
+/// Type of regex-bound actions.
+type CaptureAction<'a, R> = Box<dyn Fn(&Captures) -> R + 'a>;
+
+/// A `RegexBindings` object wraps a `Bindings` of textual
+/// events, also binding regexes that are tried in the order
+/// they were bound when an event has no exact binding. The
+/// first regex to match wins.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{Bindings, RegexBindings};
+/// let help = || "help".to_string();
+/// let mut kc: RegexBindings<String, String> = RegexBindings::new(Bindings::new());
+/// kc.bind_action("/help", &help);
+/// kc.bind_regex(r"^/give (\w+) (\d+)$", |caps| {
+///     format!("give {} x{}", &caps[1], &caps[2])
+/// }).unwrap();
+/// kc.bind_regex(r"^/", |caps| format!("unknown {}", &caps[0])).unwrap();
+/// assert_eq!(kc.run_action("/help").unwrap(), "help");
+/// assert_eq!(kc.run_action("/give sword 2").unwrap(), "give sword x2");
+/// assert_eq!(kc.run_action("/give").unwrap(), "unknown /");
+/// assert!(kc.run_action("hello").is_none());
+/// assert!(kc.bind_regex("(", |_| String::new()).is_err());
+/// ```
+pub struct RegexBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    bindings: Bindings<'a, E, R>,
+    regexes: Vec<(RegexId, Regex, CaptureAction<'a, R>)>,
+    next_id: u64,
+}
+
+impl<'a, E, R> RegexBindings<'a, E, R>
+    where E: Hash + Eq + AsRef<str>, R: 'a
+{
+    /// Add regex bindings to `bindings`.
+    pub fn new(bindings: Bindings<'a, E, R>) -> Self {
+        RegexBindings {
+            bindings,
+            regexes: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// The exact bindings.
+    pub fn bindings(&self) -> &Bindings<'a, E, R> {
+        &self.bindings
+    }
+
+    /// Overwrite or create an exact binding.
+    pub fn bind_action<T>(&mut self, event: &T, action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.bindings.bind_action(event, action);
+    }
+
+    /// Remove the exact binding for `event`, returning its
+    /// action.
+    pub fn unbind_action<T>(&mut self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.bindings.unbind_action(event)
+    }
+
+    /// Bind `pattern`, after any regexes already bound,
+    /// returning its id. Fail if `pattern` is not a valid
+    /// regex.
+    pub fn bind_regex<F>(&mut self, pattern: &str, action: F) -> Result<RegexId, regex::Error>
+        where F: Fn(&Captures) -> R + 'a
+    {
+        let regex = Regex::new(pattern)?;
+        let id = RegexId(self.next_id);
+        self.next_id += 1;
+        self.regexes.push((id, regex, Box::new(action)));
+        Ok(id)
+    }
+
+    /// Remove the regex binding with id `id`. Return false
+    /// if there is no such binding.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Bindings, RegexBindings};
+    /// let mut kc: RegexBindings<String, ()> = RegexBindings::new(Bindings::new());
+    /// let id = kc.bind_regex("", |_| ()).unwrap();
+    /// assert!(kc.run_action("anything").is_some());
+    /// assert!(kc.unbind_regex(id));
+    /// assert!(!kc.unbind_regex(id));
+    /// assert!(kc.run_action("anything").is_none());
+    /// ```
+    pub fn unbind_regex(&mut self, id: RegexId) -> bool {
+        let before = self.regexes.len();
+        self.regexes.retain(|&(i, _, _)| i != id);
+        self.regexes.len() < before
+    }
+
+    /// Given an event that is bound exactly or matched by a
+    /// regex, run the corresponding action and return the
+    /// result.  Return `None` if there is no such action.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + AsRef<str> + ?Sized
+    {
+        self.run_action_matched(event).map(|(result, _)| result)
+    }
+
+    /// As `run_action()`, but also return the regex that
+    /// matched, if the event was not bound exactly, for
+    /// logging.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Bindings, RegexBindings};
+    /// let quit = || 0;
+    /// let mut kc: RegexBindings<String, usize> = RegexBindings::new(Bindings::new());
+    /// kc.bind_action("quit", &quit);
+    /// kc.bind_regex(r"^say (.*)$", |caps| caps[1].len()).unwrap();
+    /// assert_eq!(kc.run_action_matched("quit"), Some((0, None)));
+    /// assert_eq!(kc.run_action_matched("say hi"), Some((2, Some(r"^say (.*)$"))));
+    /// ```
+    pub fn run_action_matched<T>(&self, event: &T) -> Option<(R, Option<&str>)>
+        where E: Borrow<T>, T: Hash + Eq + AsRef<str> + ?Sized
+    {
+        if let Some(action) = self.bindings.get_action(event) {
+            return Some((action(), None));
+        }
+        self.regexes.iter().find_map(|(_, regex, action)| {
+            regex
+                .captures(event.as_ref())
+                .map(|caps| (action(&caps), Some(regex.as_str())))
+        })
+    }
+}
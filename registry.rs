@@ -0,0 +1,89 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Named actions, so that bindings can be described in
+//! text: configuration files, scripts and the like.
+
+use std::collections::HashMap;
+
+use {completions, same_action, Action};
+
+/// An `ActionRegistry` maps names to actions.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{Action, ActionRegistry};
+/// let quit: Action<&str> = &|| "quit";
+/// let mut registry = ActionRegistry::new();
+/// registry.register("quit", quit);
+/// assert_eq!(registry.get("quit").unwrap()(), "quit");
+/// assert_eq!(registry.name_of(quit), Some("quit"));
+/// ```
+pub struct ActionRegistry<'a, R: 'a>(HashMap<String, Action<'a, R>>);
+
+impl<'a, R> Default for ActionRegistry<'a, R> {
+    fn default() -> Self {
+        ActionRegistry::new()
+    }
+}
+
+impl<'a, R> ActionRegistry<'a, R> {
+    /// Make a new empty registry.
+    pub fn new() -> Self {
+        ActionRegistry(HashMap::new())
+    }
+
+    /// Register `action` under `name`, returning the action
+    /// previously registered under that name if any.
+    pub fn register(&mut self, name: &str, action: Action<'a, R>)
+                    -> Option<Action<'a, R>>
+    {
+        self.0.insert(name.to_owned(), action)
+    }
+
+    /// Return the action registered under `name`.
+    pub fn get(&self, name: &str) -> Option<Action<'a, R>> {
+        self.0.get(name).copied()
+    }
+
+    /// Return a name `action` is registered under. If it
+    /// has several, return the least.
+    pub fn name_of(&self, action: Action<'a, R>) -> Option<&str> {
+        self.0
+            .iter()
+            .filter(|&(_, &a)| same_action(a, action))
+            .map(|(name, _)| name.as_str())
+            .min()
+    }
+
+    /// Return the registered names in sorted order.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.0.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    /// Return the registered names starting with `prefix`,
+    /// sorted, for completing partially typed command
+    /// names. If `ignore_case` is set, case is ignored when
+    /// matching.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, ActionRegistry};
+    /// let nop: Action<()> = &|| ();
+    /// let mut registry = ActionRegistry::new();
+    /// for name in &["save", "Save-All", "quit"] {
+    ///     registry.register(name, nop);
+    /// }
+    /// assert_eq!(registry.complete_name("sa", true), vec!["Save-All", "save"]);
+    /// assert!(registry.complete_name("x", false).is_empty());
+    /// ```
+    pub fn complete_name(&self, prefix: &str, ignore_case: bool) -> Vec<String> {
+        completions(self.0.keys().cloned(), prefix, ignore_case)
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Rewriting physical events into logical ones before
+//! lookup, so that hardware differences are kept apart
+//! from command bindings.
+
+use std::borrow::{Borrow, ToOwned};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use {Action, Bindings};
+
+/// A `Remapper` rewrites events. Events with no rewrite are
+/// left alone. Rewrites are applied once: the result of a
+/// rewrite is not itself rewritten.
+pub struct Remapper<E>(HashMap<E, E>)
+    where E: Hash + Eq;
+
+impl<E> Default for Remapper<E>
+    where E: Hash + Eq
+{
+    fn default() -> Self {
+        Remapper::new()
+    }
+}
+
+impl<E> Remapper<E>
+    where E: Hash + Eq
+{
+    /// Make a new remapper that rewrites nothing.
+    pub fn new() -> Self {
+        Remapper(HashMap::new())
+    }
+
+    /// Rewrite `from` to `to`, replacing any previous
+    /// rewrite of `from`.
+    pub fn remap<T>(&mut self, from: &T, to: &T)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.0.insert(from.to_owned(), to.to_owned());
+    }
+
+    /// Stop rewriting `from`, returning what it was
+    /// rewritten to.
+    pub fn unmap<T>(&mut self, from: &T) -> Option<E>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.0.remove(from)
+    }
+
+    /// Return the rewrite of `event`, or `event` itself if it
+    /// has none.
+    pub fn rewrite<'e, T>(&'e self, event: &'e T) -> &'e T
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.0.get(event).map(Borrow::borrow).unwrap_or(event)
+    }
+}
+
+/// A `RemappedBindings` object rewrites each event with a
+/// `Remapper` before looking it up in its `Bindings`.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{Bindings, RemappedBindings, Remapper};
+/// let ctrl = || "ctrl";
+/// let mut kc = Bindings::new();
+/// kc.bind_action("Ctrl", &ctrl);
+/// let mut remapper = Remapper::new();
+/// remapper.remap("Caps", "Ctrl");
+/// let kc: RemappedBindings<String, &str> = RemappedBindings::new(remapper, kc);
+/// assert_eq!(kc.run_action("Caps"), Some("ctrl"));
+/// assert_eq!(kc.run_action("Ctrl"), Some("ctrl"));
+/// assert!(kc.run_action("Shift").is_none());
+/// ```
+pub struct RemappedBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    remapper: Remapper<E>,
+    bindings: Bindings<'a, E, R>,
+}
+
+impl<'a, E, R> RemappedBindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Remap events with `remapper` before looking them up
+    /// in `bindings`.
+    pub fn new(remapper: Remapper<E>, bindings: Bindings<'a, E, R>) -> Self {
+        RemappedBindings { remapper, bindings }
+    }
+
+    /// The remapper.
+    pub fn remapper(&self) -> &Remapper<E> {
+        &self.remapper
+    }
+
+    /// The remapper, for changing it.
+    pub fn remapper_mut(&mut self) -> &mut Remapper<E> {
+        &mut self.remapper
+    }
+
+    /// The bindings.
+    pub fn bindings(&self) -> &Bindings<'a, E, R> {
+        &self.bindings
+    }
+
+    /// The bindings, for changing them.
+    pub fn bindings_mut(&mut self) -> &mut Bindings<'a, E, R> {
+        &mut self.bindings
+    }
+
+    /// Return the remapper and the bindings.
+    pub fn into_inner(self) -> (Remapper<E>, Bindings<'a, E, R>) {
+        (self.remapper, self.bindings)
+    }
+
+    /// Rewrite `event`, then run it as
+    /// `Bindings::run_action()` does.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.bindings.run_action(self.remapper.rewrite(event))
+    }
+
+    /// Rewrite `event`, then return the corresponding
+    /// action unexecuted. Return `None` if the rewritten
+    /// event is not bound.
+    pub fn get_action<T>(&self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.bindings.get_action(self.remapper.rewrite(event))
+    }
+}
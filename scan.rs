@@ -0,0 +1,59 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Translation of raw scancodes to symbolic key names, so
+//! that bindings can name keys rather than physical codes.
+
+use std::collections::HashMap;
+
+use Bindings;
+
+/// A `ScanTable` maps scancodes to key names.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{Bindings, ScanTable};
+/// let mut table = ScanTable::new();
+/// table.insert(0x2d, "X");
+/// assert_eq!(table.translate(0x2d), Some("X"));
+/// assert!(table.translate(0x2c).is_none());
+/// let cut = || "cut";
+/// let mut kc: Bindings<String, &str> = Bindings::new();
+/// kc.bind_action("X", &cut);
+/// assert_eq!(kc.run_scancode(&table, 0x2d), Some("cut"));
+/// assert!(kc.run_scancode(&table, 0x2c).is_none());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanTable(HashMap<u16, String>);
+
+impl ScanTable {
+    /// Make a new empty table.
+    pub fn new() -> Self {
+        ScanTable::default()
+    }
+
+    /// Translate `code` to `name`, returning the name it
+    /// previously translated to if any.
+    pub fn insert(&mut self, code: u16, name: &str) -> Option<String> {
+        self.0.insert(code, name.to_owned())
+    }
+
+    /// Return the name `code` translates to.
+    pub fn translate(&self, code: u16) -> Option<&str> {
+        self.0.get(&code).map(String::as_str)
+    }
+}
+
+impl<'a, R> Bindings<'a, String, R>
+    where R: 'a
+{
+    /// Translate `code` by `table` and run the action for
+    /// the resulting name, as by `run_action()`. Return
+    /// `None` if `table` has no translation for `code`.
+    pub fn run_scancode(&self, table: &ScanTable, code: u16) -> Option<R> {
+        table.translate(code).and_then(|name| self.run_action(name))
+    }
+}
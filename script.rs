@@ -0,0 +1,294 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! A small line-oriented language for defining bindings
+//! in rc files. Each line is blank, a `#` comment, or one
+//! of
+//!
+//! ```text
+//! bind EVENT NAME      bind EVENT to the action registered as NAME
+//! unbind EVENT         remove any binding for EVENT
+//! alias EVENT OTHER    make EVENT a synonym for OTHER, as by bind_alias()
+//! ```
+//!
+//! Words are separated by whitespace. A word containing
+//! whitespace, `"` or `#` may be written in double quotes,
+//! with `\"` and `\\` as escapes: `bind "C-x C-s" save`.
+
+use std::error::Error;
+use std::fmt;
+
+use {Action, ActionRegistry, Bindings};
+
+/// Counts of the operations performed by a script.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AppliedSummary {
+    /// `bind` commands applied.
+    pub bound: usize,
+    /// `bind` commands that replaced an existing binding,
+    /// and `alias` commands that replaced an existing
+    /// alias.
+    pub overridden: usize,
+    /// `unbind` commands that removed a binding.
+    pub unbound: usize,
+    /// `alias` commands applied.
+    pub aliased: usize,
+}
+
+/// What went wrong on a script line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptErrorKind {
+    /// The line does not start with a known command.
+    UnknownCommand(String),
+    /// The command has the wrong number of arguments.
+    WrongArgs(String),
+    /// A double-quoted word is missing its closing quote.
+    UnterminatedQuote,
+    /// No action is registered under this name.
+    UnknownAction(String),
+    /// An `alias` of this event would loop.
+    AliasLoop(String),
+}
+
+/// An error in a binding script, with the 1-based number
+/// of the offending line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    /// Line number of the error.
+    pub line: usize,
+    /// Description of the error.
+    pub kind: ScriptErrorKind,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            ScriptErrorKind::UnknownCommand(ref c) =>
+                write!(f, "unknown command {:?}", c),
+            ScriptErrorKind::WrongArgs(ref c) =>
+                write!(f, "wrong number of arguments to {}", c),
+            ScriptErrorKind::UnterminatedQuote =>
+                write!(f, "unterminated quote"),
+            ScriptErrorKind::UnknownAction(ref a) =>
+                write!(f, "unknown action {:?}", a),
+            ScriptErrorKind::AliasLoop(ref e) =>
+                write!(f, "alias {:?} would loop", e),
+        }
+    }
+}
+
+impl Error for ScriptError {}
+
+/// Split a script line into words, dropping any comment.
+fn split_line(line: &str) -> Result<Vec<String>, ScriptErrorKind> {
+    let mut words = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        let mut word = String::new();
+        match chars.peek() {
+            None | Some('#') => return Ok(words),
+            Some('"') => {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) => word.push(c),
+                            None => return Err(ScriptErrorKind::UnterminatedQuote),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(ScriptErrorKind::UnterminatedQuote),
+                    }
+                }
+            },
+            Some(_) => {
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+            },
+        }
+        words.push(word);
+    }
+}
+
+/// Write `word` so that `split_line()` reads it back.
+fn quote(word: &str) -> String {
+    let plain = !word.is_empty() && !word.contains(|c: char| {
+        c.is_whitespace() || c == '"' || c == '#' || c == '\\'
+    });
+    if plain {
+        return word.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for c in word.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl<'a, R> Bindings<'a, String, R> {
+    /// Run the binding script `text` against these
+    /// bindings, resolving action names with `registry`.
+    /// Lines are applied in order; on error, processing
+    /// stops and the lines before the bad one stay
+    /// applied. Unbinding an unbound event is not an error.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, ActionRegistry, AppliedSummary, Bindings};
+    /// let quit: Action<&str> = &|| "quit";
+    /// let save: Action<&str> = &|| "save";
+    /// let mut registry = ActionRegistry::new();
+    /// registry.register("quit", quit);
+    /// registry.register("save", save);
+    /// let mut kc = Bindings::new();
+    /// let summary = kc.apply_script(r#"
+    ///     ## My keys.
+    ///     bind q quit
+    ///     bind "C-x C-s" save
+    ///     bind q save   # changed my mind
+    ///     alias Q q
+    ///     alias W q
+    ///     bind W quit   # only an alias, so not an override
+    ///     unbind F1
+    /// "#, &registry).unwrap();
+    /// assert_eq!(summary, AppliedSummary {
+    ///     bound: 4, overridden: 1, unbound: 0, aliased: 2,
+    /// });
+    /// assert_eq!(kc.run_action("C-x C-s"), Some("save"));
+    /// assert_eq!(kc.run_action("Q"), Some("save"));
+    /// let err = kc.apply_script("alias q Q", &registry);
+    /// assert_eq!(err.unwrap_err().to_string(),
+    ///            "line 1: alias \"q\" would loop");
+    ///
+    /// let err = kc.apply_script("bind x\nbind y fly", &registry);
+    /// assert_eq!(err.unwrap_err().line, 1);
+    /// let err = kc.apply_script("bind y fly", &registry);
+    /// assert_eq!(err.unwrap_err().to_string(),
+    ///            "line 1: unknown action \"fly\"");
+    /// ```
+    pub fn apply_script(&mut self,
+                        text: &str,
+                        registry: &ActionRegistry<'a, R>)
+                        -> Result<AppliedSummary, ScriptError>
+    {
+        let mut summary = AppliedSummary::default();
+        for (i, line) in text.lines().enumerate() {
+            let error = |kind| ScriptError { line: i + 1, kind };
+            let words = split_line(line).map_err(error)?;
+            let words: Vec<&str> = words.iter().map(String::as_str).collect();
+            let wrong_args = || error(ScriptErrorKind::WrongArgs(words[0].to_string()));
+            match words.first() {
+                None => (),
+                Some(&"bind") => {
+                    if words.len() != 3 {
+                        return Err(wrong_args());
+                    }
+                    let action = registry.get(words[2]).ok_or_else(|| {
+                        error(ScriptErrorKind::UnknownAction(words[2].to_string()))
+                    })?;
+                    if self.store.contains_key(words[1]) {
+                        summary.overridden += 1;
+                    }
+                    self.bind_action(words[1], action);
+                    summary.bound += 1;
+                },
+                Some(&"unbind") => {
+                    if words.len() != 2 {
+                        return Err(wrong_args());
+                    }
+                    if self.unbind_action(words[1]).is_some() {
+                        summary.unbound += 1;
+                    }
+                },
+                Some(&"alias") => {
+                    if words.len() != 3 {
+                        return Err(wrong_args());
+                    }
+                    let replaced = self.aliases.contains_key(words[1]);
+                    self.bind_alias(words[1], words[2]).map_err(|_| {
+                        error(ScriptErrorKind::AliasLoop(words[1].to_string()))
+                    })?;
+                    if replaced {
+                        summary.overridden += 1;
+                    }
+                    summary.aliased += 1;
+                },
+                Some(command) => {
+                    let command = command.to_string();
+                    return Err(error(ScriptErrorKind::UnknownCommand(command)));
+                },
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Write the bindings out as a script that
+    /// `apply_script()` will read back, one `bind` line per
+    /// event in sorted order, then one `alias` line per
+    /// alias in sorted order. Bindings to actions not in
+    /// `registry` cannot be written as `bind` lines, so
+    /// they are listed in comments instead.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Action, ActionRegistry, Bindings};
+    /// let quit: Action<&str> = &|| "quit";
+    /// let other: Action<&str> = &|| "other";
+    /// let mut registry = ActionRegistry::new();
+    /// registry.register("quit", quit);
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action("q", quit);
+    /// kc.bind_action("C-x C-c", quit);
+    /// kc.bind_action("z", other);
+    /// kc.bind_alias("Q", "q").unwrap();
+    /// let script = kc.dump_script(&registry);
+    /// assert_eq!(script, "bind \"C-x C-c\" quit\n\
+    ///                     bind q quit\n\
+    ///                     ## z: unregistered action\n\
+    ///                     alias Q q\n");
+    /// let mut copy = Bindings::new();
+    /// copy.apply_script(&script, &registry).unwrap();
+    /// assert_eq!(copy.run_action("C-x C-c"), Some("quit"));
+    /// assert_eq!(copy.run_action("Q"), Some("quit"));
+    /// kc.unbind_action("z");
+    /// assert!(copy == kc);
+    /// ```
+    pub fn dump_script(&self, registry: &ActionRegistry<'a, R>) -> String {
+        let mut bindings: Vec<(&String, &Action<'a, R>)> = self.store.iter().collect();
+        bindings.sort_by_key(|&(event, _)| event);
+        let mut script = String::new();
+        for (event, &action) in bindings {
+            match registry.name_of(action) {
+                Some(name) => {
+                    script += &format!("bind {} {}\n", quote(event), quote(name));
+                },
+                None => {
+                    script += &format!("# {}: unregistered action\n", quote(event));
+                },
+            }
+        }
+        let mut aliases: Vec<(&String, &String)> = self.aliases.iter().collect();
+        aliases.sort();
+        for (alias, target) in aliases {
+            script += &format!("alias {} {}\n", quote(alias), quote(target));
+        }
+        script
+    }
+}
@@ -0,0 +1,465 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Recording dispatched events and replaying them, for
+//! reproducing bugs from a user's input session.
+//!
+//! With the `serde` feature, sessions can be saved and
+//! loaded as JSON lines: one record per line, each an
+//! object with an `offset` from the start of the session
+//! (as `secs` and `nanos`) and the `event`.
+//!
+//! Plain event logs, with one event per line and no
+//! times, can be replayed with `Bindings::replay_log()`
+//! and `Bindings::replay_log_text()`.
+
+use std::hash::Hash;
+use std::thread;
+use std::time::{Duration, Instant};
+#[cfg(feature = "serde")]
+use std::fs::File;
+#[cfg(feature = "serde")]
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use {Bindings, Source};
+
+/// A dispatched event and when it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Record<E> {
+    /// Time since the start of the session.
+    pub offset: Duration,
+    /// The event.
+    pub event: E,
+}
+
+/// A `SessionRecorder` dispatches events to bindings,
+/// recording each event and its time.
+///
+/// # Examples:
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use kbehdz::{Bindings, SessionRecorder};
+/// let one = || 1;
+/// let mut kc = Bindings::new();
+/// kc.bind_action("a", &one);
+/// let start = Instant::now();
+/// let mut recorder = SessionRecorder::starting_at(start);
+/// assert_eq!(recorder.dispatch_at(&kc, "a".to_string(), start), Some(1));
+/// let later = start + Duration::from_millis(5);
+/// assert_eq!(recorder.dispatch_at(&kc, "b".to_string(), later), None);
+/// let records = recorder.records();
+/// assert_eq!(records.len(), 2);
+/// assert_eq!(records[1].offset, Duration::from_millis(5));
+/// assert_eq!(records[1].event, "b");
+/// ```
+pub struct SessionRecorder<E> {
+    start: Instant,
+    records: Vec<Record<E>>,
+}
+
+impl<E> Default for SessionRecorder<E> {
+    fn default() -> Self {
+        SessionRecorder::new()
+    }
+}
+
+impl<E> SessionRecorder<E> {
+    /// Start recording a session now.
+    pub fn new() -> Self {
+        SessionRecorder::starting_at(Instant::now())
+    }
+
+    /// Start recording a session that began at `start`.
+    pub fn starting_at(start: Instant) -> Self {
+        SessionRecorder {
+            start,
+            records: Vec::new(),
+        }
+    }
+
+    /// Record `event` as happening now and dispatch it to
+    /// `bindings`, returning the result.
+    pub fn dispatch<'a, R>(&mut self, bindings: &Bindings<'a, E, R>, event: E)
+                           -> Option<R>
+        where E: Hash + Eq
+    {
+        self.dispatch_at(bindings, event, Instant::now())
+    }
+
+    /// Record `event` as happening at `when` and dispatch it
+    /// to `bindings`, returning the result. Times before
+    /// the start of the session are recorded as the start.
+    pub fn dispatch_at<'a, R>(&mut self,
+                              bindings: &Bindings<'a, E, R>,
+                              event: E,
+                              when: Instant)
+                              -> Option<R>
+        where E: Hash + Eq
+    {
+        let result = bindings.run_action(&event);
+        let offset = when.saturating_duration_since(self.start);
+        self.records.push(Record { offset, event });
+        result
+    }
+
+    /// The records so far, oldest first.
+    pub fn records(&self) -> &[Record<E>] {
+        &self.records
+    }
+
+    /// Stop recording, returning the session for replay.
+    pub fn into_replay(self) -> SessionReplay<E> {
+        SessionReplay::new(self.records)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<E: Serialize> SessionRecorder<E> {
+    /// Write the records so far as JSON lines.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for record in &self.records {
+            serde_json::to_writer(&mut writer, record)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()
+    }
+
+    /// Save the records so far as JSON lines in the file
+    /// at `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+}
+
+/// How fast to replay a session.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Speed {
+    /// Dispatch events one after another without waiting.
+    Unpaced,
+    /// Wait between events as long as the original
+    /// session did.
+    Original,
+    /// Wait between events as long as the original session
+    /// did, divided by the given factor. A factor that is
+    /// not a positive finite number is taken as `Unpaced`.
+    Scaled(f64),
+}
+
+/// What happened when replaying a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayReport<E, R> {
+    /// Result of dispatching each record, in order.
+    pub results: Vec<Option<R>>,
+    /// Index and event of each record whose event is no
+    /// longer bound.
+    pub unbound: Vec<(usize, E)>,
+}
+
+/// A recorded session, ready to replay.
+pub struct SessionReplay<E> {
+    records: Vec<Record<E>>,
+}
+
+impl<E> SessionReplay<E> {
+    /// Make a replay of `records`, which should be in time
+    /// order.
+    pub fn new(records: Vec<Record<E>>) -> Self {
+        SessionReplay { records }
+    }
+
+    /// The recorded records.
+    pub fn records(&self) -> &[Record<E>] {
+        &self.records
+    }
+
+    /// Make a `Replayer` for stepping through the session.
+    pub fn into_replayer(self) -> Replayer<E> {
+        Replayer::new(self.records)
+    }
+
+    /// Dispatch the recorded events to `bindings` in order,
+    /// paced according to `speed`. Report each result, and
+    /// every event that is no longer bound, so that drift
+    /// between the recorded and current bindings shows up.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::{Bindings, SessionRecorder, Speed};
+    /// let one = || 1;
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action(&'a', &one);
+    /// kc.bind_action(&'b', &one);
+    /// let mut recorder = SessionRecorder::new();
+    /// for event in "abba".chars() {
+    ///     recorder.dispatch(&kc, event);
+    /// }
+    /// kc.unbind_action(&'b');
+    /// let replay = recorder.into_replay();
+    /// let report = replay.replay(&kc, Speed::Unpaced);
+    /// assert_eq!(report.results, vec![Some(1), None, None, Some(1)]);
+    /// assert_eq!(report.unbound, vec![(1, 'b'), (2, 'b')]);
+    /// assert_eq!(replay.replay(&kc, Speed::Scaled(0.0)), report);
+    /// ```
+    pub fn replay<'a, R>(&self, bindings: &Bindings<'a, E, R>, speed: Speed)
+                         -> ReplayReport<E, R>
+        where E: Hash + Eq + Clone
+    {
+        let start = Instant::now();
+        let mut report = ReplayReport {
+            results: Vec::with_capacity(self.records.len()),
+            unbound: Vec::new(),
+        };
+        for (i, record) in self.records.iter().enumerate() {
+            let due = match speed {
+                Speed::Unpaced => None,
+                Speed::Original => Some(record.offset),
+                Speed::Scaled(factor) if factor > 0.0 && factor.is_finite() => {
+                    let secs = record.offset.as_secs_f64() / factor;
+                    Some(Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX))
+                },
+                Speed::Scaled(_) => None,
+            };
+            if let Some(due) = due {
+                let elapsed = start.elapsed();
+                if due > elapsed {
+                    thread::sleep(due - elapsed);
+                }
+            }
+            let result = bindings.run_action_reporting(&record.event);
+            match result {
+                Some((_, Source::Bound)) => (),
+                _ => report.unbound.push((i, record.event.clone())),
+            }
+            report.results.push(result.map(|(result, _)| result));
+        }
+        report
+    }
+}
+
+/// A `Replayer` steps through a recorded session under
+/// control, for finding which event of a long session went
+/// wrong. It keeps a virtual clock, independent of wall
+/// time, that starts at the start of the session.
+///
+/// Seeking backward restarts from the first record, so it
+/// only reproduces the session if the caller first resets
+/// whatever state the actions change, and the actions
+/// depend on nothing else: not wall time, randomness or
+/// outside input.
+///
+/// # Examples:
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::time::Duration;
+/// use kbehdz::{Bindings, Record, Replayer};
+/// let total = Cell::new(0);
+/// let add = || { total.set(total.get() + 1); total.get() };
+/// let mut kc = Bindings::new();
+/// kc.bind_action(&'+', &add);
+/// let records = (0..4)
+///     .map(|i| Record { offset: Duration::from_secs(i * 10), event: '+' })
+///     .collect();
+/// let mut replayer = Replayer::new(records);
+/// let (record, result) = replayer.step(&kc).unwrap();
+/// assert_eq!((record.offset, result), (Duration::from_secs(0), Some(1)));
+/// assert_eq!(replayer.run_for(&kc, Duration::from_secs(15)), vec![Some(2)]);
+/// assert_eq!(replayer.clock(), Duration::from_secs(15));
+/// total.set(0);
+/// replayer.seek(&kc, 1);
+/// assert_eq!((replayer.position(), total.get()), (1, 1));
+/// replayer.seek(&kc, 4);
+/// assert!(replayer.is_finished());
+/// assert_eq!(total.get(), 4);
+/// ```
+pub struct Replayer<E> {
+    records: Vec<Record<E>>,
+    position: usize,
+    clock: Duration,
+}
+
+impl<E> Replayer<E> {
+    /// Make a replayer positioned at the start of
+    /// `records`, which should be in time order.
+    pub fn new(records: Vec<Record<E>>) -> Self {
+        Replayer {
+            records,
+            position: 0,
+            clock: Duration::from_secs(0),
+        }
+    }
+
+    /// The recorded records.
+    pub fn records(&self) -> &[Record<E>] {
+        &self.records
+    }
+
+    /// Index of the next record to be dispatched.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Current time of the virtual clock, since the start
+    /// of the session.
+    pub fn clock(&self) -> Duration {
+        self.clock
+    }
+
+    /// True if every record has been dispatched.
+    pub fn is_finished(&self) -> bool {
+        self.position == self.records.len()
+    }
+
+    /// Go back to the start of the session without
+    /// dispatching anything.
+    pub fn rewind(&mut self) {
+        self.position = 0;
+        self.clock = Duration::from_secs(0);
+    }
+
+    /// Dispatch the next record to `bindings`, advancing the
+    /// clock to its time if it is later. Return the record
+    /// and the result, or `None` if the session is over.
+    pub fn step<'a, R>(&mut self, bindings: &Bindings<'a, E, R>)
+                       -> Option<(&Record<E>, Option<R>)>
+        where E: Hash + Eq
+    {
+        let record = self.records.get(self.position)?;
+        self.position += 1;
+        self.clock = self.clock.max(record.offset);
+        Some((record, bindings.run_action(&record.event)))
+    }
+
+    /// Advance the clock by `duration`, dispatching every
+    /// record that falls due, and return their results.
+    pub fn run_for<'a, R>(&mut self, bindings: &Bindings<'a, E, R>, duration: Duration)
+                          -> Vec<Option<R>>
+        where E: Hash + Eq
+    {
+        let until = self.clock + duration;
+        let mut results = Vec::new();
+        while self.records.get(self.position).is_some_and(|r| r.offset <= until) {
+            if let Some((_, result)) = self.step(bindings) {
+                results.push(result);
+            }
+        }
+        self.clock = until;
+        results
+    }
+
+    /// Dispatch records until `index` is the next one,
+    /// restarting from the first record if `index` is
+    /// behind the current position, and return the results.
+    /// Indices past the end stop at the end.
+    pub fn seek<'a, R>(&mut self, bindings: &Bindings<'a, E, R>, index: usize)
+                       -> Vec<Option<R>>
+        where E: Hash + Eq
+    {
+        let index = index.min(self.records.len());
+        if index < self.position {
+            self.rewind();
+        }
+        let mut results = Vec::new();
+        while self.position < index {
+            if let Some((_, result)) = self.step(bindings) {
+                results.push(result);
+            }
+        }
+        results
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<E: DeserializeOwned> SessionReplay<E> {
+    /// Read a session written by
+    /// `SessionRecorder::write_to()`. Blank lines are
+    /// skipped.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::time::Instant;
+    /// use kbehdz::{Bindings, SessionRecorder, SessionReplay, Speed};
+    /// let one = || 1;
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action("X", &one);
+    /// let mut recorder = SessionRecorder::new();
+    /// recorder.dispatch(&kc, "X".to_string());
+    /// recorder.dispatch(&kc, "Y".to_string());
+    /// let mut saved = Vec::new();
+    /// recorder.write_to(&mut saved).unwrap();
+    /// let replay: SessionReplay<String> =
+    ///     SessionReplay::read_from(&saved[..]).unwrap();
+    /// assert_eq!(replay.records(), recorder.records());
+    /// let report = replay.replay(&kc, Speed::Original);
+    /// assert_eq!(report.unbound, vec![(1, "Y".to_string())]);
+    /// ```
+    pub fn read_from<B: BufRead>(reader: B) -> io::Result<Self> {
+        let mut records = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            records.push(record);
+        }
+        Ok(SessionReplay::new(records))
+    }
+
+    /// Load a session saved by `SessionRecorder::save()`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        SessionReplay::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<'a, E, R> Bindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Dispatch each event of `log` in order, returning the
+    /// results. Unbound events give `None`.
+    pub fn replay_log(&self, log: &[E]) -> Vec<Option<R>> {
+        log.iter().map(|event| self.run_action(event)).collect()
+    }
+}
+
+impl<'a, R> Bindings<'a, String, R>
+    where R: 'a
+{
+    /// Dispatch the events of a newline-separated event log
+    /// in order, returning the results. Each line is one
+    /// event; trailing carriage returns and blank lines are
+    /// ignored.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let one = || 1;
+    /// let three = || 3;
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action("X", &one);
+    /// kc.bind_action("Z", &three);
+    /// let results = kc.replay_log_text("X\nY\nZ");
+    /// assert_eq!(results, vec![Some(1), None, Some(3)]);
+    /// ```
+    pub fn replay_log_text(&self, log: &str) -> Vec<Option<R>> {
+        let events: Vec<String> = log
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_owned)
+            .collect();
+        self.replay_log(&events)
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Bindings that can be sent and shared between threads.
+
+use std::borrow::{Borrow, ToOwned};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// Type of actions in a `SharedBindings`.
+pub type SharedAction<R> = Arc<dyn Fn() -> R + Send + Sync>;
+
+/// A `SharedBindings` object is like an `OwnedBindings`,
+/// but its actions are `Send` and `Sync` and reference
+/// counted, so it can move to another thread and be cloned
+/// cheaply.
+///
+/// # Examples:
+///
+/// ```
+/// use std::thread;
+/// use kbehdz::SharedBindings;
+/// let mut kc = SharedBindings::new();
+/// let greeting = "hello".to_string();
+/// kc.bind_fn("greet", move || greeting.clone());
+/// let worker = kc.clone();
+/// let result = thread::spawn(move || worker.run_action("greet"))
+///     .join()
+///     .unwrap();
+/// assert_eq!(result.unwrap(), "hello");
+/// assert!(kc.run_action("greet").is_some());
+/// ```
+pub struct SharedBindings<E, R>(HashMap<E, SharedAction<R>>)
+    where E: Hash + Eq;
+
+impl<E, R> Clone for SharedBindings<E, R>
+    where E: Hash + Eq + Clone
+{
+    fn clone(&self) -> Self {
+        SharedBindings(self.0.clone())
+    }
+}
+
+impl<E, R> Default for SharedBindings<E, R>
+    where E: Hash + Eq
+{
+    fn default() -> Self {
+        SharedBindings::new()
+    }
+}
+
+impl<E, R> SharedBindings<E, R>
+    where E: Hash + Eq
+{
+    /// Make a new empty binding.
+    pub fn new() -> Self {
+        SharedBindings(HashMap::new())
+    }
+
+    /// Overwrite or create a binding. The event must be
+    /// passed by reference: it will be converted to an
+    /// owned type.
+    pub fn bind_action<T>(&mut self, event: &T, action: SharedAction<R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.0.insert(event.to_owned(), action);
+    }
+
+    /// Overwrite or create a binding to a closure.
+    pub fn bind_fn<T, F>(&mut self, event: &T, f: F)
+        where E: Borrow<T>,
+              T: ToOwned<Owned=E> + ?Sized,
+              F: Fn() -> R + Send + Sync + 'static
+    {
+        self.bind_action(event, Arc::new(f));
+    }
+
+    /// Remove the binding for `event`, returning its action.
+    pub fn unbind_action<T>(&mut self, event: &T) -> Option<SharedAction<R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.0.remove(event)
+    }
+
+    /// Given an event that is in the bindings, run the
+    /// corresponding action and return the result.  Return
+    /// `None` if no such event is bound.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.0.get(event).map(|action| action())
+    }
+
+    /// Given an event that is in the bindings, return the
+    /// corresponding action unexecuted.  Return `None` if
+    /// no such event is bound.
+    pub fn get_action<T>(&self, event: &T) -> Option<SharedAction<R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.0.get(event).cloned()
+    }
+}
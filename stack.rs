@@ -0,0 +1,174 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Layered keymaps: a stack of `Bindings` in which upper
+//! layers override lower ones.
+
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+use {Action, Bindings};
+
+/// A `KeymapStack` is a stack of `Bindings` layers over a
+/// base layer. An event is looked up from the top layer
+/// down, and the first layer binding it wins. Binding
+/// `noop()` in an upper layer disables an event of a lower
+/// one.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{noop, Bindings, KeymapStack};
+/// let walk = || "walk";
+/// let swim = || "swim";
+/// let mut base = Bindings::new();
+/// base.bind_action(&'w', &walk);
+/// base.bind_action(&'j', &walk);
+/// let mut water = Bindings::new();
+/// water.bind_action(&'w', &swim);
+/// water.bind_action(&'j', noop());
+/// let mut stack = KeymapStack::new(base);
+/// stack.push(water);
+/// assert_eq!(stack.run_action(&'w'), Some("swim"));
+/// assert_eq!(stack.run_action(&'j'), Some(""));
+/// stack.pop();
+/// assert_eq!(stack.run_action(&'w'), Some("walk"));
+/// assert!(stack.pop().is_none());
+/// ```
+pub struct KeymapStack<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    base: Bindings<'a, E, R>,
+    layers: Vec<Bindings<'a, E, R>>,
+}
+
+impl<'a, E, R> KeymapStack<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Make a stack with `base` as its only layer.
+    pub fn new(base: Bindings<'a, E, R>) -> Self {
+        KeymapStack {
+            base,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Push `layer` onto the top of the stack.
+    pub fn push(&mut self, layer: Bindings<'a, E, R>) {
+        self.layers.push(layer);
+    }
+
+    /// Pop the top layer and return it. The base layer is
+    /// never popped: return `None` if it is the only layer.
+    pub fn pop(&mut self) -> Option<Bindings<'a, E, R>> {
+        self.layers.pop()
+    }
+
+    /// Number of layers, including the base layer.
+    pub fn depth(&self) -> usize {
+        self.layers.len() + 1
+    }
+
+    /// The base layer.
+    pub fn base(&self) -> &Bindings<'a, E, R> {
+        &self.base
+    }
+
+    /// The top layer, for changing it.
+    pub fn top_mut(&mut self) -> &mut Bindings<'a, E, R> {
+        self.layers.last_mut().unwrap_or(&mut self.base)
+    }
+
+    /// The layers from the top down.
+    fn layers(&self) -> impl Iterator<Item=&Bindings<'a, E, R>> {
+        self.layers.iter().rev().chain(Some(&self.base))
+    }
+
+    /// Given an event that is bound in some layer, run the
+    /// action of the topmost such layer and return the
+    /// result. Return `None` if no layer binds the event.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.get_action(event).map(|action| action())
+    }
+
+    /// Given an event that is bound in some layer, return
+    /// the action of the topmost such layer unexecuted.
+    /// Return `None` if no layer binds the event.
+    pub fn get_action<T>(&self, event: &T) -> Option<Action<'a, R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.layers().find_map(|layer| layer.get_action(event))
+    }
+}
+
+/// Build a `KeymapStack` from named groups of
+/// `event => action` pairs, as for `bindings!`, bottom
+/// layer first. The names are only labels for the reader.
+///
+/// # Examples:
+///
+/// ```
+/// #[macro_use] extern crate kbehdz;
+/// # fn main() {
+/// let left = || "left";
+/// let quit = || "quit";
+/// let delete = || "delete";
+/// let insert_h = || "insert h";
+/// let stack = layers! {
+///     normal {
+///         &'h' => &left,
+///         &'q' => &quit,
+///     },
+///     insert {
+///         &'h' => &insert_h,
+///         &'\x08' => &delete,
+///     },
+/// };
+/// assert_eq!(stack.depth(), 2);
+/// assert_eq!(stack.run_action(&'h'), Some("insert h"));
+/// assert_eq!(stack.run_action(&'q'), Some("quit"));
+/// assert_eq!(stack.base().run_action(&'h'), Some("left"));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! layers {
+    ($base:ident { $($base_event:expr => $base_action:expr),* $(,)? }
+     $(, $layer:ident { $($event:expr => $action:expr),* $(,)? })* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut stack = $crate::KeymapStack::new(
+            $crate::bindings! { $($base_event => $base_action),* }
+        );
+        $(stack.push($crate::bindings! { $($event => $action),* });)*
+        stack
+    }};
+}
+
+impl<'a, E, R> Bindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Make a `KeymapStack` with these bindings as its
+    /// base layer.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let one = || 1;
+    /// let two = || 2;
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action("a", &one);
+    /// kc.bind_action("b", &two);
+    /// let stack = kc.into_stack();
+    /// assert_eq!(stack.depth(), 1);
+    /// assert_eq!(stack.run_action("a"), Some(1));
+    /// assert_eq!(stack.run_action("b"), Some(2));
+    /// assert!(stack.run_action("c").is_none());
+    /// ```
+    pub fn into_stack(self) -> KeymapStack<'a, E, R> {
+        KeymapStack::new(self)
+    }
+}
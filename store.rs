@@ -0,0 +1,221 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Map storage for bindings that adapts to its size.
+//!
+//! Most keymaps are small: a handful of bindings per mode.
+//! For these a linear scan of a vector beats hashing, and
+//! needs no hash table allocation. A `Store` starts out as
+//! a vector and turns into a `HashMap` once it grows past
+//! `SMALL_MAX` entries. It stays a `HashMap` after that,
+//! even if it shrinks, so that a map hovering around the
+//! threshold does not keep converting back and forth.
+
+use std::borrow::Borrow;
+use std::collections::hash_map::{self, HashMap};
+use std::hash::Hash;
+use std::slice;
+use std::vec;
+
+/// Most entries kept in the vector representation.
+pub const SMALL_MAX: usize = 8;
+
+/// A map from `K` to `V`.
+pub enum Store<K, V> {
+    Small(Vec<(K, V)>),
+    Large(HashMap<K, V>),
+}
+
+impl<K, V> Store<K, V>
+    where K: Hash + Eq
+{
+    /// Make a new empty store.
+    pub fn new() -> Self {
+        Store::Small(Vec::new())
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        match *self {
+            Store::Small(ref entries) => entries.len(),
+            Store::Large(ref map) => map.len(),
+        }
+    }
+
+    /// Look up the entry for `key`.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+        where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        match *self {
+            Store::Small(ref entries) => {
+                entries
+                    .iter()
+                    .find(|&(k, _)| k.borrow() == key)
+                    .map(|(k, v)| (k, v))
+            },
+            Store::Large(ref map) => map.get_key_value(key),
+        }
+    }
+
+    /// Look up the value for `key`.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+        where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    /// True if there is an entry for `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+        where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        self.get_key_value(key).is_some()
+    }
+
+    /// Set the value for `key`, returning the old value if
+    /// any.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let promote = match *self {
+            Store::Small(ref mut entries) => {
+                if let Some(entry) = entries.iter_mut().find(|(k, _)| *k == key) {
+                    return Some(std::mem::replace(&mut entry.1, value));
+                }
+                entries.push((key, value));
+                entries.len() > SMALL_MAX
+            },
+            Store::Large(ref mut map) => return map.insert(key, value),
+        };
+        if promote {
+            let map = match std::mem::replace(self, Store::Large(HashMap::new())) {
+                Store::Small(entries) => entries.into_iter().collect(),
+                Store::Large(map) => map,
+            };
+            *self = Store::Large(map);
+        }
+        None
+    }
+
+    /// Remove the entry for `key`, returning its value if
+    /// any.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+        where K: Borrow<Q>, Q: Hash + Eq + ?Sized
+    {
+        match *self {
+            Store::Small(ref mut entries) => {
+                let posn = entries.iter().position(|(k, _)| k.borrow() == key)?;
+                Some(entries.swap_remove(posn).1)
+            },
+            Store::Large(ref mut map) => map.remove(key),
+        }
+    }
+
+    /// Keep only the entries for which `f` returns true.
+    pub fn retain<F>(&mut self, mut f: F)
+        where F: FnMut(&K, &mut V) -> bool
+    {
+        match *self {
+            Store::Small(ref mut entries) => entries.retain_mut(|(k, v)| f(k, v)),
+            Store::Large(ref mut map) => map.retain(f),
+        }
+    }
+
+    /// Remove the entries for which `f` returns true,
+    /// returning them.
+    pub fn extract_if<F>(&mut self, mut f: F) -> Vec<(K, V)>
+        where F: FnMut(&K) -> bool
+    {
+        match *self {
+            Store::Small(ref mut entries) => {
+                let (extracted, kept) = std::mem::take(entries)
+                    .into_iter()
+                    .partition(|(k, _)| f(k));
+                *entries = kept;
+                extracted
+            },
+            Store::Large(ref mut map) => {
+                let mut extracted = Vec::new();
+                for (k, v) in std::mem::take(map) {
+                    if f(&k) {
+                        extracted.push((k, v));
+                    } else {
+                        map.insert(k, v);
+                    }
+                }
+                extracted
+            },
+        }
+    }
+
+    /// Iterate over the entries in no particular order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        match *self {
+            Store::Small(ref entries) => Iter::Small(entries.iter()),
+            Store::Large(ref map) => Iter::Large(map.iter()),
+        }
+    }
+
+    /// Iterate over the keys in no particular order.
+    pub fn keys(&self) -> impl Iterator<Item=&K> {
+        self.iter().map(|(k, _)| k)
+    }
+}
+
+/// Iterator over the entries of a `Store`.
+pub enum Iter<'s, K: 's, V: 's> {
+    Small(slice::Iter<'s, (K, V)>),
+    Large(hash_map::Iter<'s, K, V>),
+}
+
+impl<'s, K, V> Iterator for Iter<'s, K, V> {
+    type Item = (&'s K, &'s V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match *self {
+            Iter::Small(ref mut entries) => entries.next().map(|(k, v)| (k, v)),
+            Iter::Large(ref mut entries) => entries.next(),
+        }
+    }
+}
+
+impl<'s, K, V> IntoIterator for &'s Store<K, V>
+    where K: Hash + Eq
+{
+    type Item = (&'s K, &'s V);
+    type IntoIter = Iter<'s, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Owning iterator over the entries of a `Store`.
+pub enum IntoIter<K, V> {
+    Small(vec::IntoIter<(K, V)>),
+    Large(hash_map::IntoIter<K, V>),
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match *self {
+            IntoIter::Small(ref mut entries) => entries.next(),
+            IntoIter::Large(ref mut entries) => entries.next(),
+        }
+    }
+}
+
+impl<K, V> IntoIterator for Store<K, V>
+    where K: Hash + Eq
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            Store::Small(entries) => IntoIter::Small(entries.into_iter()),
+            Store::Large(map) => IntoIter::Large(map.into_iter()),
+        }
+    }
+}
@@ -0,0 +1,237 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Dual-role keys, as in keyboard firmware: tapping a key
+//! runs one action, holding it past a threshold runs
+//! another.
+//!
+//! A press of a dual-role key is undecided until one of:
+//!
+//! * the key is released before its threshold: a tap;
+//! * the threshold passes with the key still down: a hold;
+//! * another key is pressed first, under
+//!   `Interrupt::Hold`: a hold.
+//!
+//! Under `Interrupt::Wait`, keys pressed while a dual-role
+//! key is undecided are held back until it is decided, and
+//! then run in order after its action.
+
+use std::borrow::{Borrow, ToOwned};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use {Action, Bindings};
+
+/// A key edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edge<E> {
+    /// The key went down.
+    Press(E),
+    /// The key came up.
+    Release(E),
+}
+
+/// What a press of another key does to an undecided
+/// dual-role key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Interrupt {
+    /// Nothing: the dual-role key is decided only at its
+    /// release or threshold.
+    Wait,
+    /// Decide the dual-role key as a hold at once
+    /// ("permissive hold").
+    Hold,
+}
+
+/// The actions of a dual-role key.
+struct DualRole<'a, R: 'a> {
+    tap: Action<'a, R>,
+    hold: Action<'a, R>,
+    threshold: Duration,
+}
+
+/// A `TapHold` object dispatches timestamped key edges,
+/// running dual-role actions for dual-role keys and the
+/// actions of its `Bindings` for presses of other keys.
+///
+/// # Examples:
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use kbehdz::{Bindings, Edge, Interrupt, TapHold};
+/// let jump = || "jump";
+/// let crouch = || "crouch";
+/// let fire = || "fire";
+/// let mut kc = Bindings::new();
+/// kc.bind_action(&'f', &fire);
+/// let mut th = TapHold::new(kc, Interrupt::Wait);
+/// th.bind_dual(&' ', &jump, &crouch, Duration::from_millis(200));
+/// let t = Instant::now();
+/// let ms = |n| t + Duration::from_millis(n);
+///
+/// // A quick tap.
+/// assert!(th.feed(Edge::Press(' '), ms(0)).is_empty());
+/// assert_eq!(th.feed(Edge::Release(' '), ms(50)), vec!["jump"]);
+///
+/// // A hold, decided by a later edge or by polling.
+/// assert!(th.feed(Edge::Press(' '), ms(100)).is_empty());
+/// assert!(th.poll(ms(250)).is_empty());
+/// assert_eq!(th.poll(ms(300)), vec!["crouch"]);
+/// assert!(th.feed(Edge::Release(' '), ms(400)).is_empty());
+///
+/// // Other keys are held back until the tap is decided.
+/// assert!(th.feed(Edge::Press(' '), ms(500)).is_empty());
+/// assert!(th.feed(Edge::Press('f'), ms(550)).is_empty());
+/// assert_eq!(th.feed(Edge::Release(' '), ms(600)), vec!["jump", "fire"]);
+/// assert_eq!(th.feed(Edge::Press('f'), ms(700)), vec!["fire"]);
+/// ```
+pub struct TapHold<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    bindings: Bindings<'a, E, R>,
+    duals: HashMap<E, DualRole<'a, R>>,
+    interrupt: Interrupt,
+    pending: Option<(E, Instant)>,
+    held_back: Vec<E>,
+}
+
+impl<'a, E, R> TapHold<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Dispatch presses of ordinary keys through
+    /// `bindings`, treating presses of other keys during a
+    /// dual-role press as `interrupt` says.
+    pub fn new(bindings: Bindings<'a, E, R>, interrupt: Interrupt) -> Self {
+        TapHold {
+            bindings,
+            duals: HashMap::new(),
+            interrupt,
+            pending: None,
+            held_back: Vec::new(),
+        }
+    }
+
+    /// The bindings for ordinary keys.
+    pub fn bindings(&self) -> &Bindings<'a, E, R> {
+        &self.bindings
+    }
+
+    /// Overwrite or create a dual-role binding: `tap` runs
+    /// when `event` is released within `threshold` of being
+    /// pressed, and `hold` runs otherwise.
+    pub fn bind_dual<T>(&mut self, event: &T, tap: Action<'a, R>, hold: Action<'a, R>,
+                        threshold: Duration)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        let role = DualRole { tap, hold, threshold };
+        self.duals.insert(event.to_owned(), role);
+    }
+
+    /// Remove the dual-role binding for `event`. Return
+    /// false if there was none.
+    pub fn unbind_dual<T>(&mut self, event: &T) -> bool
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.duals.remove(event).is_some()
+    }
+
+    /// Decide an undecided dual-role key whose threshold
+    /// has passed at time `now` as a hold, returning the
+    /// results of the actions run. This should be called
+    /// periodically when no edges arrive.
+    pub fn poll(&mut self, now: Instant) -> Vec<R> {
+        let expired = match self.pending {
+            // A key unbound while undecided is decided at
+            // once, running nothing.
+            Some((ref event, start)) => self.duals
+                .get(event)
+                .is_none_or(|role| now.duration_since(start) >= role.threshold),
+            None => false,
+        };
+        let mut results = Vec::new();
+        if expired {
+            self.decide(false, now, &mut results);
+        }
+        results
+    }
+
+    /// Dispatch `edge`, which happened at time `now`,
+    /// returning the results of the actions run, in order.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use kbehdz::{Bindings, Edge, Interrupt, TapHold};
+    /// let escape = || "escape";
+    /// let control = || "control";
+    /// let copy = || "c";
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action(&'c', &copy);
+    /// let mut th = TapHold::new(kc, Interrupt::Hold);
+    /// th.bind_dual(&'⇪', &escape, &control, Duration::from_millis(200));
+    /// let t = Instant::now();
+    /// let ms = |n| t + Duration::from_millis(n);
+    /// assert!(th.feed(Edge::Press('⇪'), ms(0)).is_empty());
+    /// assert_eq!(th.feed(Edge::Press('c'), ms(20)), vec!["control", "c"]);
+    /// assert!(th.feed(Edge::Release('c'), ms(40)).is_empty());
+    /// assert!(th.feed(Edge::Release('⇪'), ms(60)).is_empty());
+    /// ```
+    pub fn feed(&mut self, edge: Edge<E>, now: Instant) -> Vec<R> {
+        let mut results = self.poll(now);
+        match edge {
+            Edge::Press(event) => self.press(event, now, &mut results),
+            Edge::Release(event) => {
+                let tapped = match self.pending {
+                    Some((ref pending, _)) => *pending == event,
+                    None => false,
+                };
+                if tapped {
+                    self.decide(true, now, &mut results);
+                }
+            },
+        }
+        results
+    }
+
+    /// Dispatch a press of `event` at time `now`.
+    fn press(&mut self, event: E, now: Instant, results: &mut Vec<R>) {
+        if let Some((ref pending, _)) = self.pending {
+            if *pending == event {
+                // Key repeat of the undecided key.
+                return;
+            }
+            match self.interrupt {
+                Interrupt::Wait => {
+                    self.held_back.push(event);
+                    return;
+                },
+                Interrupt::Hold => self.decide(false, now, results),
+            }
+        }
+        if self.duals.contains_key(&event) {
+            self.pending = Some((event, now));
+        } else if let Some(result) = self.bindings.run_action(&event) {
+            results.push(result);
+        }
+    }
+
+    /// Decide the undecided dual-role key as a tap or a
+    /// hold, then dispatch the keys held back behind it.
+    fn decide(&mut self, tap: bool, now: Instant, results: &mut Vec<R>) {
+        let event = match self.pending.take() {
+            Some((event, _)) => event,
+            None => return,
+        };
+        if let Some(role) = self.duals.get(&event) {
+            results.push(if tap { (role.tap)() } else { (role.hold)() });
+        }
+        let held_back = std::mem::take(&mut self.held_back);
+        for event in held_back {
+            self.press(event, now, results);
+        }
+    }
+}
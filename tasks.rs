@@ -0,0 +1,235 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Running actions on background threads, so that heavy
+//! actions do not block the input loop.
+
+use std::borrow::{Borrow, ToOwned};
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::thread::{self, JoinHandle};
+
+use {SharedAction, SharedBindings};
+
+impl<E, R> SharedBindings<E, R>
+    where E: Hash + Eq, R: Send + 'static
+{
+    /// Given an event that is in the bindings, run the
+    /// corresponding action on a new thread and return its
+    /// handle.  Return `None` if no such event is bound.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::SharedBindings;
+    /// let mut kc = SharedBindings::new();
+    /// kc.bind_fn("sum", || (1..=100).sum::<u32>());
+    /// let task = kc.run_action_spawned("sum").unwrap();
+    /// assert_eq!(task.join().unwrap(), 5050);
+    /// assert!(kc.run_action_spawned("product").is_none());
+    /// ```
+    pub fn run_action_spawned<T>(&self, event: &T) -> Option<JoinHandle<R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.get_action(event).map(spawn)
+    }
+}
+
+/// Run `action` on a new thread.
+fn spawn<R: Send + 'static>(action: SharedAction<R>) -> JoinHandle<R> {
+    thread::spawn(move || action())
+}
+
+/// What a `TaskTracker` does with a dispatch of an event
+/// whose previous task is still running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DispatchPolicy {
+    /// Start another task alongside it.
+    Parallel,
+    /// Drop the dispatch.
+    Coalesce,
+    /// Start the task once the earlier ones are done.
+    Queue,
+}
+
+/// What `TaskTracker::dispatch()` did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dispatched {
+    /// A task was started.
+    Started,
+    /// The dispatch was dropped, since a task for the event
+    /// is running.
+    Coalesced,
+    /// The dispatch was queued behind a running task for
+    /// the event.
+    Queued,
+}
+
+/// Outcome of a finished task: its result, or the payload
+/// of its panic.
+pub type TaskOutcome<E, R> = (E, thread::Result<R>);
+
+/// A `TaskTracker` object runs actions of `SharedBindings`
+/// on background threads, keeping their handles by event.
+/// Finished tasks are collected by `reap()`, which also
+/// starts queued tasks, or by `join_all()`.
+///
+/// # Examples:
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+/// use kbehdz::{DispatchPolicy, Dispatched, SharedBindings, TaskTracker};
+/// let gate = Arc::new(Mutex::new(()));
+/// let held = gate.lock().unwrap();
+/// let mut kc = SharedBindings::new();
+/// let export_gate = Arc::clone(&gate);
+/// kc.bind_fn("export", move || {
+///     drop(export_gate.lock());
+///     "exported"
+/// });
+/// let mut tasks = TaskTracker::new(DispatchPolicy::Coalesce);
+/// assert_eq!(tasks.dispatch(&kc, "export"), Some(Dispatched::Started));
+/// assert_eq!(tasks.dispatch(&kc, "export"), Some(Dispatched::Coalesced));
+/// assert_eq!(tasks.in_flight("export"), 1);
+/// assert!(tasks.dispatch(&kc, "import").is_none());
+/// drop(held);
+/// let done = tasks.join_all();
+/// assert_eq!(done.len(), 1);
+/// assert_eq!(done[0].1.as_ref().unwrap(), &"exported");
+/// assert_eq!(tasks.in_flight("export"), 0);
+/// ```
+pub struct TaskTracker<E, R>
+    where E: Hash + Eq
+{
+    policy: DispatchPolicy,
+    running: HashMap<E, Vec<JoinHandle<R>>>,
+    queued: HashMap<E, VecDeque<SharedAction<R>>>,
+    finished: Vec<TaskOutcome<E, R>>,
+}
+
+impl<E, R> TaskTracker<E, R>
+    where E: Hash + Eq + Clone, R: Send + 'static
+{
+    /// Make a tracker with no tasks, treating dispatches
+    /// of events with running tasks as `policy` says.
+    pub fn new(policy: DispatchPolicy) -> Self {
+        TaskTracker {
+            policy,
+            running: HashMap::new(),
+            queued: HashMap::new(),
+            finished: Vec::new(),
+        }
+    }
+
+    /// Run the action for `event` in `bindings` on a new
+    /// thread, unless the policy says otherwise. Return
+    /// `None` if no such event is bound.
+    pub fn dispatch<T>(&mut self, bindings: &SharedBindings<E, R>, event: &T)
+                       -> Option<Dispatched>
+        where E: Borrow<T>, T: ToOwned<Owned=E> + Hash + Eq + ?Sized
+    {
+        let action = bindings.get_action(event)?;
+        let event = event.to_owned();
+        self.collect_finished(&event);
+        self.start_queued(&event);
+        let busy = self.running.contains_key::<E>(&event) || self.queued.contains_key::<E>(&event);
+        let dispatched = match self.policy {
+            DispatchPolicy::Coalesce if busy => Dispatched::Coalesced,
+            DispatchPolicy::Queue if busy => {
+                self.queued.entry(event).or_default().push_back(action);
+                Dispatched::Queued
+            },
+            _ => {
+                self.running.entry(event).or_default().push(spawn(action));
+                Dispatched::Started
+            },
+        };
+        Some(dispatched)
+    }
+
+    /// Number of tasks for `event` started and not yet
+    /// collected. Some may have finished.
+    pub fn in_flight<T>(&self, event: &T) -> usize
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.running.get(event).map_or(0, Vec::len)
+    }
+
+    /// Number of tasks for `event` waiting to start.
+    pub fn queued<T>(&self, event: &T) -> usize
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.queued.get(event).map_or(0, VecDeque::len)
+    }
+
+    /// Move the finished tasks of `event` to the finished
+    /// list.
+    fn collect_finished(&mut self, event: &E) {
+        if let Some(handles) = self.running.get_mut(event) {
+            let mut i = 0;
+            while i < handles.len() {
+                if handles[i].is_finished() {
+                    let outcome = handles.swap_remove(i).join();
+                    self.finished.push((event.clone(), outcome));
+                } else {
+                    i += 1;
+                }
+            }
+            if handles.is_empty() {
+                self.running.remove(event);
+            }
+        }
+    }
+
+    /// Start the next queued task of `event` if none is
+    /// running.
+    fn start_queued(&mut self, event: &E) {
+        if self.running.contains_key(event) {
+            return;
+        }
+        if let Some(queue) = self.queued.get_mut(event) {
+            if let Some(action) = queue.pop_front() {
+                self.running.insert(event.clone(), vec![spawn(action)]);
+            }
+            if queue.is_empty() {
+                self.queued.remove(event);
+            }
+        }
+    }
+
+    /// Collect the tasks that have finished, without
+    /// waiting, and start queued tasks that can now run.
+    /// Return the outcomes of the finished tasks.
+    pub fn reap(&mut self) -> Vec<TaskOutcome<E, R>> {
+        let events: Vec<E> = self.running.keys().cloned().collect();
+        for event in &events {
+            self.collect_finished(event);
+        }
+        let events: Vec<E> = self.queued.keys().cloned().collect();
+        for event in &events {
+            self.start_queued(event);
+        }
+        std::mem::take(&mut self.finished)
+    }
+
+    /// Wait for every task, including queued ones, to
+    /// finish, and return their outcomes. Queued tasks of
+    /// an event run one after another, in order.
+    pub fn join_all(&mut self) -> Vec<TaskOutcome<E, R>> {
+        while !self.running.is_empty() || !self.queued.is_empty() {
+            let running = std::mem::take(&mut self.running);
+            for (event, handles) in running {
+                for handle in handles {
+                    self.finished.push((event.clone(), handle.join()));
+                }
+            }
+            let events: Vec<E> = self.queued.keys().cloned().collect();
+            for event in &events {
+                self.start_queued(event);
+            }
+        }
+        std::mem::take(&mut self.finished)
+    }
+}
@@ -0,0 +1,342 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Helpers for testing code that uses this crate.
+
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::rc::Rc;
+
+#[cfg(feature = "proptest")]
+use proptest::collection::vec;
+#[cfg(feature = "proptest")]
+use proptest::prop_oneof;
+#[cfg(feature = "proptest")]
+use proptest::strategy::Strategy;
+
+use combinators::constant;
+#[cfg(feature = "proptest")]
+use Action;
+use {same_action, Bindings};
+
+/// Check that `bindings` obeys the crate's invariants,
+/// panicking with a description of the first violation.
+/// This lets custom event types be checked against the
+/// same guarantees as the built-in ones. For each bound
+/// event:
+///
+/// * looking it up finds its action;
+/// * unbinding it returns that action, after which it is
+///   unbound;
+/// * binding the action again restores the original state.
+///
+/// Actions are never run. The bindings are left as they
+/// were found.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::Bindings;
+/// use kbehdz::testing::check_invariants;
+/// #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// struct Chord(u8, char);
+/// let act = || ();
+/// let mut kc = Bindings::new();
+/// kc.bind_action(&Chord(1, 'a'), &act);
+/// kc.bind_action(&Chord(2, 'a'), &act);
+/// check_invariants(&mut kc);
+/// assert!(kc.run_action(&Chord(1, 'a')).is_some());
+/// ```
+pub fn check_invariants<'a, E, R>(bindings: &mut Bindings<'a, E, R>)
+    where E: Hash + Eq + Clone + Debug, R: 'a
+{
+    let events: Vec<E> = bindings.store.keys().cloned().collect();
+    let count = events.len();
+    for event in events {
+        let action = bindings.get_action(&event).unwrap_or_else(|| {
+            panic!("bound event {:?} has no action", event)
+        });
+        let removed = bindings.unbind_action(&event).unwrap_or_else(|| {
+            panic!("bound event {:?} could not be unbound", event)
+        });
+        assert!(same_action(action, removed),
+                "unbinding {:?} returned a different action", event);
+        // An alias or fallback may still find an action.
+        let covered = bindings.aliases.contains_key(&event);
+        assert!(covered || bindings.get_action(&event).is_none(),
+                "event {:?} still bound after unbinding", event);
+        assert!(covered || bindings.fallback().is_some()
+                || bindings.run_action(&event).is_none(),
+                "event {:?} still runs after unbinding", event);
+        bindings.bind_action(&event, action);
+        let restored = bindings.get_action(&event).unwrap_or_else(|| {
+            panic!("rebinding {:?} did not bind it", event)
+        });
+        assert!(same_action(action, restored),
+                "rebinding {:?} bound a different action", event);
+    }
+    assert_eq!(bindings.store.len(), count, "bindings changed size");
+}
+
+/// A step of a dispatch script, as made by
+/// `arbitrary_dispatch_script()`.
+#[cfg(feature = "proptest")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptStep<E> {
+    /// Bind the event to the action with this index, taken
+    /// modulo the number of actions.
+    Bind(E, usize),
+    /// Unbind the event.
+    Unbind(E),
+    /// Make the first event an alias of the second.
+    Alias(E, E),
+    /// Dispatch the event.
+    Dispatch(E),
+}
+
+/// Make a strategy generating dispatch scripts of at most
+/// `len` steps, with events drawn from `events`. Scripts
+/// shrink toward fewer steps and simpler events.
+#[cfg(feature = "proptest")]
+pub fn arbitrary_dispatch_script<E, S>(events: S, len: usize)
+                                       -> impl Strategy<Value=Vec<ScriptStep<E>>>
+    where S: Strategy<Value=E> + Clone, E: Debug + Clone
+{
+    let step = prop_oneof![
+        (events.clone(), 0..8_usize).prop_map(|(event, i)| ScriptStep::Bind(event, i)),
+        events.clone().prop_map(ScriptStep::Unbind),
+        (events.clone(), events.clone()).prop_map(|(alias, to)| ScriptStep::Alias(alias, to)),
+        events.prop_map(ScriptStep::Dispatch),
+    ];
+    vec(step, 0..=len)
+}
+
+/// Run `script` against `bindings`, binding the actions of
+/// `actions`, which must not be empty. After each step,
+/// check the invariants of `check_invariants()`, and also
+/// that
+///
+/// * dispatching never panics, unless the action does;
+/// * an unbound event with no alias dispatches nothing,
+///   unless there is a fallback.
+///
+/// # Examples:
+///
+/// ```
+/// #[macro_use] extern crate proptest;
+/// extern crate kbehdz;
+/// use kbehdz::{Action, Bindings};
+/// use kbehdz::testing::{arbitrary_dispatch_script, run_dispatch_script};
+/// # fn main() {
+/// let actions: [Action<u8>; 2] = [&|| 0, &|| 1];
+/// proptest!(|(script in arbitrary_dispatch_script(0..4_u8, 20))| {
+///     let mut kc = Bindings::new();
+///     run_dispatch_script(&mut kc, &actions, &script);
+/// });
+/// # }
+/// ```
+#[cfg(feature = "proptest")]
+pub fn run_dispatch_script<'a, E, R>(bindings: &mut Bindings<'a, E, R>,
+                                     actions: &[Action<'a, R>],
+                                     script: &[ScriptStep<E>])
+    where E: Hash + Eq + Clone + Debug, R: 'a
+{
+    assert!(!actions.is_empty(), "no actions to bind");
+    for step in script {
+        match *step {
+            ScriptStep::Bind(ref event, i) => {
+                bindings.bind_action(event, actions[i % actions.len()]);
+            },
+            ScriptStep::Unbind(ref event) => {
+                bindings.unbind_action(event);
+                let covered = bindings.aliases.contains_key(event)
+                    || bindings.fallback().is_some();
+                assert!(covered || bindings.run_action(event).is_none(),
+                        "event {:?} still runs after unbinding", event);
+            },
+            ScriptStep::Alias(ref alias, ref target) => {
+                let _ = bindings.bind_alias(alias, target);
+            },
+            ScriptStep::Dispatch(ref event) => {
+                bindings.run_action(event);
+            },
+        }
+        check_invariants(bindings);
+    }
+}
+
+/// Handle for inspecting a spy action made by
+/// `spy_action()` or `spy_returning()`.
+#[derive(Debug, Clone)]
+pub struct SpyHandle(Rc<Cell<usize>>);
+
+impl SpyHandle {
+    /// Number of times the spy has been called since it
+    /// was made or last reset.
+    pub fn call_count(&self) -> usize {
+        self.0.get()
+    }
+
+    /// True if the spy has been called since it was made or
+    /// last reset.
+    pub fn called(&self) -> bool {
+        self.call_count() > 0
+    }
+
+    /// Reset the call count to zero.
+    pub fn reset(&self) {
+        self.0.set(0)
+    }
+}
+
+/// Make an action that does nothing but count its calls,
+/// along with a handle for checking the count.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::Bindings;
+/// use kbehdz::testing::spy_action;
+/// let (spy, handle) = spy_action();
+/// let mut kc = Bindings::new();
+/// kc.bind_action("a", &spy);
+/// assert!(!handle.called());
+/// kc.run_action("a");
+/// kc.run_action("a");
+/// assert_eq!(handle.call_count(), 2);
+/// handle.reset();
+/// assert!(!handle.called());
+/// ```
+pub fn spy_action() -> (impl Fn(), SpyHandle) {
+    spy_returning(())
+}
+
+/// Make an action that counts its calls and returns a
+/// clone of `value`, along with a handle for checking the
+/// count.
+pub fn spy_returning<R: Clone>(value: R) -> (impl Fn() -> R, SpyHandle) {
+    let count = Rc::new(Cell::new(0));
+    let handle = SpyHandle(Rc::clone(&count));
+    let spy = move || {
+        count.set(count.get() + 1);
+        value.clone()
+    };
+    (spy, handle)
+}
+
+/// Make an action that panics with `msg` when run.
+///
+/// # Examples:
+///
+/// ```
+/// use std::panic::catch_unwind;
+/// use kbehdz::testing::failing_action;
+/// let fail = failing_action::<usize>("boom");
+/// assert!(catch_unwind(|| fail()).is_err());
+/// ```
+pub fn failing_action<R>(msg: &str) -> impl Fn() -> R {
+    let msg = msg.to_string();
+    move || panic!("{}", msg)
+}
+
+/// Make an action that returns a clone of `value`. This is
+/// `combinators::constant()`, under the name used by the
+/// other test actions here.
+pub fn const_action<'a, R: Clone + 'a>(value: R) -> impl Fn() -> R + 'a {
+    constant(value)
+}
+
+/// Describe the bound events of `bindings`, sorted by their
+/// debug form, for use in failure messages.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::Bindings;
+/// use kbehdz::testing::{bound_events, const_action};
+/// let one = const_action(1);
+/// let mut kc = Bindings::new();
+/// kc.bind_action(&'b', &one);
+/// kc.bind_action(&'a', &one);
+/// assert_eq!(bound_events(&kc), "['a', 'b']");
+/// ```
+pub fn bound_events<'a, E, R>(bindings: &Bindings<'a, E, R>) -> String
+    where E: Hash + Eq + Debug, R: 'a
+{
+    let mut events: Vec<String> = bindings.store
+        .keys()
+        .map(|event| format!("{:?}", event))
+        .collect();
+    events.sort();
+    format!("[{}]", events.join(", "))
+}
+
+/// Assert that an event is bound, listing the bound events
+/// if it is not.
+///
+/// # Examples:
+///
+/// ```
+/// #[macro_use] extern crate kbehdz;
+/// use kbehdz::Bindings;
+/// use kbehdz::testing::const_action;
+/// # fn main() {
+/// let one = const_action(1);
+/// let mut kc = Bindings::new();
+/// kc.bind_action("a", &one);
+/// assert_bound!(kc, "a");
+/// assert_dispatches!(kc, "a", 1);
+/// # }
+/// ```
+///
+/// ```should_panic
+/// #[macro_use] extern crate kbehdz;
+/// use kbehdz::Bindings;
+/// # fn main() {
+/// let kc: Bindings<String, usize> = Bindings::new();
+/// assert_bound!(kc, "a");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_bound {
+    ($bindings:expr, $event:expr) => {
+        match (&$bindings, $event) {
+            (bindings, event) => {
+                if bindings.get_action(event).is_none() {
+                    panic!("event {:?} is not bound; bound events: {}",
+                           event,
+                           $crate::testing::bound_events(bindings));
+                }
+            }
+        }
+    };
+}
+
+/// Assert that dispatching an event runs an action that
+/// returns the expected result, listing the bound events
+/// if the event is not bound.
+#[macro_export]
+macro_rules! assert_dispatches {
+    ($bindings:expr, $event:expr, $expected:expr) => {
+        match (&$bindings, $event, $expected) {
+            (bindings, event, expected) => {
+                match bindings.run_action(event) {
+                    Some(result) => {
+                        if result != expected {
+                            panic!("event {:?} returned {:?}, expected {:?}",
+                                   event, result, expected);
+                        }
+                    },
+                    None => {
+                        panic!("event {:?} is not bound; bound events: {}",
+                               event,
+                               $crate::testing::bound_events(bindings));
+                    },
+                }
+            }
+        }
+    };
+}
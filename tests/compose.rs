@@ -0,0 +1,57 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Check that `Compose` neither loses nor reorders
+//! characters, whatever mix of sequences, aborted sequences
+//! and plain characters it is fed.
+
+extern crate kbehdz;
+
+use kbehdz::{Compose, ComposeResult};
+
+/// Feed all of `typed` and flush, returning the output.
+fn compose_all(compose: &mut Compose, typed: &str) -> String {
+    let mut output = String::new();
+    for c in typed.chars() {
+        match compose.feed(c) {
+            ComposeResult::Pending => (),
+            ComposeResult::Composed(c) => output.push(c),
+            ComposeResult::Passthrough(cs) => output.extend(cs),
+        }
+    }
+    output.extend(compose.flush());
+    output
+}
+
+#[test]
+fn composes_default_sequences() {
+    let mut compose = Compose::default();
+    assert_eq!(compose_all(&mut compose, "caf´e na¨ive ¸ca"), "café naïve ça");
+    assert_eq!(compose_all(&mut compose, "´ ` ^ "), "´`^");
+}
+
+#[test]
+fn aborted_sequences_replay_in_order() {
+    let mut compose = Compose::default();
+    // Abort by a plain character.
+    assert_eq!(compose_all(&mut compose, "a~zb"), "a~zb");
+    // Abort by another dead key, which then composes.
+    assert_eq!(compose_all(&mut compose, "´¨u"), "´ü");
+    // Abort by a chain of dead keys.
+    assert_eq!(compose_all(&mut compose, "´`^~x"), "´`^~x");
+    // Unfinished at the end.
+    assert_eq!(compose_all(&mut compose, "x´"), "x´");
+}
+
+#[test]
+fn aborted_long_sequences_replay_in_order() {
+    let mut compose = Compose::new();
+    compose.add("<=>", '⇔');
+    compose.add("<-", '←');
+    assert_eq!(compose_all(&mut compose, "<=>"), "⇔");
+    assert_eq!(compose_all(&mut compose, "<=x<-"), "<=x←");
+    assert_eq!(compose_all(&mut compose, "<=<-"), "<=←");
+    assert_eq!(compose_all(&mut compose, "<<-"), "<←");
+}
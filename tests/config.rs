@@ -0,0 +1,160 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Check loading a binding config through a chain of
+//! migrations. Version 2 renamed the `write` action to
+//! `save`; version 3 split each `key` string like
+//! `"Ctrl+S"` into a `chord` of modifiers and a key.
+
+#![cfg(feature = "serde")]
+
+extern crate kbehdz;
+extern crate serde;
+extern crate serde_json;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use kbehdz::{ConfigError, ConfigSchema};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Chord {
+    modifiers: Vec<String>,
+    key: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Binding {
+    chord: Chord,
+    action: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Config {
+    bindings: Vec<Binding>,
+}
+
+/// The bindings array of `config`, for changing in place.
+fn bindings(config: &mut Value) -> Result<&mut Vec<Value>, String> {
+    config["bindings"].as_array_mut().ok_or_else(|| "no bindings".to_string())
+}
+
+fn rename_write(mut config: Value) -> Result<Value, String> {
+    for binding in bindings(&mut config)? {
+        if binding["action"] == "write" {
+            binding["action"] = "save".into();
+        }
+    }
+    Ok(config)
+}
+
+fn split_key(mut config: Value) -> Result<Value, String> {
+    for binding in bindings(&mut config)? {
+        let key = binding["key"].as_str().ok_or_else(|| "no key".to_string())?.to_string();
+        let mut parts: Vec<&str> = key.split('+').collect();
+        let key = parts.pop().unwrap();
+        binding["chord"] = serde_json::json!({ "modifiers": parts, "key": key });
+        binding.as_object_mut().unwrap().remove("key");
+    }
+    Ok(config)
+}
+
+fn schema() -> ConfigSchema {
+    let mut schema = ConfigSchema::new(3);
+    schema.register_migration(1, 2, rename_write);
+    schema.register_migration(2, 3, split_key);
+    schema
+}
+
+fn expected() -> Config {
+    let binding = |modifiers: &[&str], key: &str, action: &str| Binding {
+        chord: Chord {
+            modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+            key: key.to_string(),
+        },
+        action: action.to_string(),
+    };
+    Config {
+        bindings: vec![
+            binding(&["Ctrl"], "S", "save"),
+            binding(&["Ctrl", "Shift"], "Q", "quit"),
+            binding(&[], "F1", "help"),
+        ],
+    }
+}
+
+#[test]
+fn v1_migrates_to_v3() {
+    let v1 = r#"{
+        "version": 1,
+        "config": { "bindings": [
+            { "key": "Ctrl+S", "action": "write" },
+            { "key": "Ctrl+Shift+Q", "action": "quit" },
+            { "key": "F1", "action": "help" }
+        ] }
+    }"#;
+    let config: Config = schema().read_from(v1.as_bytes()).unwrap();
+    assert_eq!(config, expected());
+}
+
+#[test]
+fn v2_migrates_to_v3() {
+    let v2 = r#"{
+        "version": 2,
+        "config": { "bindings": [
+            { "key": "Ctrl+S", "action": "save" },
+            { "key": "Ctrl+Shift+Q", "action": "quit" },
+            { "key": "F1", "action": "help" }
+        ] }
+    }"#;
+    let config: Config = schema().read_from(v2.as_bytes()).unwrap();
+    assert_eq!(config, expected());
+}
+
+#[test]
+fn v3_round_trips() {
+    let mut saved = Vec::new();
+    schema().write_to(&expected(), &mut saved).unwrap();
+    let envelope: Value = serde_json::from_slice(&saved).unwrap();
+    assert_eq!(envelope["version"], 3);
+    let config: Config = schema().read_from(&saved[..]).unwrap();
+    assert_eq!(config, expected());
+}
+
+#[test]
+fn future_version_fails() {
+    let v4 = r#"{ "version": 4, "config": { "bindings": [] } }"#;
+    let err = schema().read_from::<Config, _>(v4.as_bytes()).unwrap_err();
+    assert_eq!(err.to_string(), "config version 4 is newer than supported version 3");
+}
+
+#[test]
+fn out_of_range_version_fails() {
+    let huge = r#"{ "version": 4294967296, "config": { "bindings": [] } }"#;
+    match schema().read_from::<Config, _>(huge.as_bytes()) {
+        Err(ConfigError::BadVersion(4294967296)) => (),
+        r => panic!("unexpected {:?}", r),
+    }
+}
+
+#[test]
+fn failed_migration_reported() {
+    let v1 = r#"{ "version": 1, "config": {} }"#;
+    match schema().read_from::<Config, _>(v1.as_bytes()) {
+        Err(ConfigError::Migration { from: 1, to: 2, ref message }) => {
+            assert_eq!(message, "no bindings");
+        },
+        r => panic!("unexpected {:?}", r),
+    }
+}
+
+#[test]
+fn missing_migration_reported() {
+    let v0 = r#"{ "version": 0, "config": {} }"#;
+    match schema().read_from::<Config, _>(v0.as_bytes()) {
+        Err(ConfigError::NoMigration(0)) => (),
+        r => panic!("unexpected {:?}", r),
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Check syncing bindings through a serialized
+//! `BindingsDelta`, as between two machines.
+
+#![cfg(feature = "serde")]
+
+extern crate kbehdz;
+extern crate serde_json;
+
+use kbehdz::{ActionRegistry, Bindings, BindingsDelta, DeltaError};
+
+#[test]
+fn delta_syncs_through_json() {
+    let save = || "save";
+    let quit = || "quit";
+    let help = || "help";
+    let mut registry = ActionRegistry::new();
+    registry.register("save", &save);
+    registry.register("quit", &quit);
+    registry.register("help", &help);
+    let mut laptop: Bindings<String, &str> = Bindings::new();
+    laptop.bind_action("C-s", &save);
+    laptop.bind_action("C-q", &quit);
+    let base = laptop.subset(&["C-s", "C-q"]);
+    let mut desktop = laptop.subset(&["C-s", "C-q"]);
+
+    laptop.unbind_action("C-q");
+    laptop.bind_action("C-s", &help);
+    laptop.bind_action("F1", &help);
+    let delta = laptop.delta_since(&base, &registry).unwrap();
+    assert_eq!(delta.removed, vec![("C-q".to_string(), "quit".to_string())]);
+    assert_eq!(delta.changed,
+               vec![("C-s".to_string(), "save".to_string(), "help".to_string())]);
+    assert_eq!(delta.added, vec![("F1".to_string(), "help".to_string())]);
+
+    let sent = serde_json::to_string(&delta).unwrap();
+    let received: BindingsDelta<String> = serde_json::from_str(&sent).unwrap();
+    assert_eq!(received, delta);
+    desktop.apply_delta(&received, &registry).unwrap();
+    assert!(desktop.delta_since(&laptop, &registry).unwrap().is_empty());
+}
+
+#[test]
+fn unknown_and_unnamed_actions_reported() {
+    let save = || "save";
+    let secret = || "secret";
+    let mut registry = ActionRegistry::new();
+    registry.register("save", &save);
+    let mut kc: Bindings<String, &str> = Bindings::new();
+    kc.bind_action("C-x", &secret);
+    let errors = kc.delta_since(&Bindings::new(), &registry).unwrap_err();
+    assert_eq!(errors, vec![DeltaError::Unnamed("C-x".to_string())]);
+
+    let delta = BindingsDelta {
+        added: vec![("C-s".to_string(), "save".to_string()),
+                    ("C-o".to_string(), "open".to_string())],
+        ..BindingsDelta::default()
+    };
+    let errors = kc.apply_delta(&delta, &registry).unwrap_err();
+    assert_eq!(errors, vec![DeltaError::UnknownAction("open".to_string())]);
+    assert!(kc.run_action("C-s").is_none());
+}
@@ -0,0 +1,34 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Check the crate invariants over random dispatch scripts,
+//! with and without a fallback action.
+
+#![cfg(feature = "proptest")]
+
+#[macro_use]
+extern crate proptest;
+extern crate kbehdz;
+
+use kbehdz::testing::{arbitrary_dispatch_script, run_dispatch_script};
+use kbehdz::{Action, Bindings};
+
+proptest! {
+    #[test]
+    fn scripts_keep_invariants(script in arbitrary_dispatch_script("[a-c]{1,2}", 40)) {
+        let actions: [Action<u8>; 3] = [&|| 0, &|| 1, &|| 2];
+        let mut kc: Bindings<String, u8> = Bindings::new();
+        run_dispatch_script(&mut kc, &actions, &script);
+    }
+
+    #[test]
+    fn scripts_keep_invariants_with_fallback(script in arbitrary_dispatch_script(0..6_u8, 40)) {
+        let actions: [Action<u8>; 2] = [&|| 0, &|| 1];
+        let other: Action<u8> = &|| 9;
+        let mut kc = Bindings::new();
+        kc.set_fallback(other);
+        run_dispatch_script(&mut kc, &actions, &script);
+    }
+}
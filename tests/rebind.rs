@@ -0,0 +1,75 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Check that rebinding through a wrapper or a capture
+//! calls the rebind callback of the bindings, once per
+//! rebinding.
+
+extern crate kbehdz;
+
+use std::cell::RefCell;
+
+use kbehdz::{Action, Bindings, CaptureOutcome, NormalizeKey, NormalizingBindings,
+             ObservedBindings, RebindCapture};
+
+/// A key name compared without regard to case.
+struct Key<'s>(&'s str);
+
+impl<'s> NormalizeKey<String> for Key<'s> {
+    fn normalize(&self) -> String {
+        self.0.to_lowercase()
+    }
+}
+
+#[test]
+fn normalizing_rebind_calls_callback_once() {
+    let walk = || "walk";
+    let run = || "run";
+    let rebound = RefCell::new(Vec::new());
+    let note = |event: &String| rebound.borrow_mut().push(event.clone());
+    let mut kc: Bindings<String, &str> = Bindings::new();
+    kc.set_on_rebind(&note);
+    let mut nb = NormalizingBindings::new(kc);
+    nb.bind_action(&Key("W"), &walk);
+    assert!(rebound.borrow().is_empty());
+    nb.bind_action(&Key("w"), &run);
+    assert_eq!(*rebound.borrow(), vec!["w".to_string()]);
+    assert_eq!(nb.run_action(&Key("W")), Some("run"));
+}
+
+#[test]
+fn observed_rebind_calls_callback_once() {
+    let walk = || "walk";
+    let run = || "run";
+    let rebound = RefCell::new(Vec::new());
+    let note = |event: &char| rebound.borrow_mut().push(*event);
+    let mut kc: Bindings<char, &str> = Bindings::new();
+    kc.set_on_rebind(&note);
+    let mut ob = ObservedBindings::new(kc);
+    ob.bind_action(&'w', &walk);
+    assert!(rebound.borrow().is_empty());
+    ob.bind_action(&'w', &run);
+    assert_eq!(*rebound.borrow(), vec!['w']);
+    assert_eq!(ob.run_action(&'w'), Some("run"));
+}
+
+#[test]
+fn stolen_binding_calls_callback_once() {
+    let jump: Action<&str> = &|| "jump";
+    let fire: Action<&str> = &|| "fire";
+    let rebound = RefCell::new(Vec::new());
+    let note = |event: &char| rebound.borrow_mut().push(*event);
+    let mut kc: Bindings<char, &str> = Bindings::new();
+    kc.bind_action(&' ', jump);
+    kc.bind_action(&'f', fire);
+    kc.set_on_rebind(&note);
+    let mut capture = RebindCapture::new('\x1b');
+    capture.start(jump);
+    assert_eq!(capture.feed(&mut kc, 'f'), CaptureOutcome::Conflict { existing_event: 'f' });
+    assert!(rebound.borrow().is_empty());
+    assert_eq!(capture.confirm_steal(&mut kc), Some('f'));
+    assert_eq!(*rebound.borrow(), vec!['f']);
+    assert_eq!(kc.run_action(&'f'), Some("jump"));
+}
@@ -0,0 +1,136 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Check that `Bindings` behaves like a plain `HashMap`
+//! through random sequences of binds and unbinds that
+//! carry it back and forth across the small-map threshold.
+
+extern crate kbehdz;
+
+use std::collections::HashMap;
+
+use kbehdz::{ActionRegistry, Bindings};
+
+/// Small deterministic pseudo-random number generator, so
+/// failures are reproducible.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self.0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % bound
+    }
+}
+
+#[test]
+fn bindings_agree_with_hashmap() {
+    let actions: Vec<Box<dyn Fn() -> u64>> =
+        (0..4_u64).map(|n| Box::new(move || n) as Box<dyn Fn() -> u64>).collect();
+    for seed in 0..20 {
+        let mut rng = Lcg(seed);
+        let mut kc = Bindings::new();
+        let mut model: HashMap<u64, u64> = HashMap::new();
+        // Keep the key range near the threshold so both
+        // representations get exercised.
+        let keys = 4 + seed * 2;
+        for step in 0..2000 {
+            let event = rng.next(keys);
+            if rng.next(3) == 0 {
+                let removed = kc.unbind_action(&event).map(|action| action());
+                assert_eq!(removed, model.remove(&event),
+                           "seed {} step {}: unbind {}", seed, step, event);
+            } else {
+                let n = rng.next(4);
+                kc.bind_action(&event, &*actions[n as usize]);
+                model.insert(event, n);
+            }
+            for probe in 0..keys {
+                assert_eq!(kc.run_action(&probe), model.get(&probe).cloned(),
+                           "seed {} step {}: lookup {}", seed, step, probe);
+            }
+            let mut bound: Vec<(u64, u64)> = kc.to_vec()
+                .into_iter()
+                .map(|(event, action)| (event, action()))
+                .collect();
+            bound.sort();
+            let mut expected: Vec<(u64, u64)> = model.iter()
+                .map(|(&event, &n)| (event, n))
+                .collect();
+            expected.sort();
+            assert_eq!(bound, expected, "seed {} step {}", seed, step);
+        }
+    }
+}
+
+#[test]
+fn extract_if_agrees_with_hashmap() {
+    let act = || ();
+    // Sizes on both sides of the threshold.
+    for size in &[5_u64, 50] {
+        let mut kc = Bindings::new();
+        for event in 0..*size {
+            kc.bind_action(&event, &act);
+        }
+        let mut extracted: Vec<u64> = kc.extract_if(|event| event % 3 == 0)
+            .into_iter()
+            .map(|(event, _)| event)
+            .collect();
+        extracted.sort();
+        let expected: Vec<u64> = (0..*size).filter(|event| event % 3 == 0).collect();
+        assert_eq!(extracted, expected, "size {}", size);
+        for event in 0..*size {
+            assert_eq!(kc.run_action(&event).is_some(), event % 3 != 0,
+                       "size {}: lookup {}", size, event);
+        }
+        assert_eq!(kc.len() as u64, size - expected.len() as u64);
+    }
+}
+
+#[test]
+fn into_iter_yields_every_binding() {
+    let actions: Vec<Box<dyn Fn() -> u64>> =
+        (0..4_u64).map(|n| Box::new(move || n) as Box<dyn Fn() -> u64>).collect();
+    // Sizes on both sides of the threshold.
+    for size in &[5_u64, 50] {
+        let mut kc = Bindings::new();
+        for event in 0..*size {
+            kc.bind_action(&event, &*actions[(event % 4) as usize]);
+        }
+        let mut bound: Vec<(u64, u64)> = kc.into_iter()
+            .map(|(event, action)| (event, action()))
+            .collect();
+        assert_eq!(bound.len() as u64, *size);
+        bound.sort();
+        let expected: Vec<(u64, u64)> = (0..*size).map(|event| (event, event % 4)).collect();
+        assert_eq!(bound, expected, "size {}", size);
+    }
+}
+
+#[test]
+fn content_hash_ignores_order() {
+    let actions: Vec<Box<dyn Fn() -> u64>> =
+        (0..4_u64).map(|n| Box::new(move || n) as Box<dyn Fn() -> u64>).collect();
+    let mut registry = ActionRegistry::new();
+    for (n, action) in actions.iter().enumerate() {
+        registry.register(&format!("action-{}", n), &**action);
+    }
+    // Sizes on both sides of the threshold.
+    for size in &[5_u64, 50] {
+        let mut forward = Bindings::new();
+        for event in 0..*size {
+            forward.bind_action(&event, &*actions[(event % 4) as usize]);
+        }
+        let mut backward = Bindings::new();
+        for event in (0..*size).rev() {
+            backward.bind_action(&event, &*actions[(event % 4) as usize]);
+        }
+        let hash = forward.content_hash(&registry);
+        assert_eq!(hash, backward.content_hash(&registry), "size {}", size);
+        backward.bind_action(&0, &*actions[1]);
+        assert_ne!(hash, backward.content_hash(&registry), "size {}", size);
+    }
+}
@@ -0,0 +1,20 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Benchmark helper bindings.
+
+#![cfg(feature = "bench-helpers")]
+
+extern crate kbehdz;
+
+use kbehdz::sample_bindings;
+
+#[test]
+fn sample_bindings_have_requested_size() {
+    let kc = sample_bindings(100);
+    assert_eq!(kc.len(), 100);
+    assert_eq!(kc.run_action("event-6"), Some(2));
+    assert!(kc.run_action("event-100").is_none());
+}
@@ -0,0 +1,112 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Check `TaskTracker` dispatch policies with slow actions.
+//! Each action waits on a gate held by the test, so tasks
+//! are known to be running until the test opens the gate.
+
+extern crate kbehdz;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use kbehdz::{DispatchPolicy, Dispatched, SharedBindings, TaskTracker};
+
+/// Bindings with one slow action, `"slow"`, which waits on
+/// `gate` and returns the number of runs so far, counting
+/// itself.
+fn slow_bindings(gate: &Arc<Mutex<()>>) -> SharedBindings<String, usize> {
+    let gate = Arc::clone(gate);
+    let runs = AtomicUsize::new(0);
+    let mut kc = SharedBindings::new();
+    kc.bind_fn("slow", move || {
+        drop(gate.lock());
+        runs.fetch_add(1, Ordering::SeqCst) + 1
+    });
+    kc
+}
+
+/// Results of `outcomes`, sorted.
+fn results(outcomes: Vec<(String, thread::Result<usize>)>) -> Vec<usize> {
+    let mut results: Vec<usize> = outcomes
+        .into_iter()
+        .map(|(event, outcome)| {
+            assert_eq!(event, "slow");
+            outcome.unwrap()
+        })
+        .collect();
+    results.sort();
+    results
+}
+
+#[test]
+fn parallel_starts_every_dispatch() {
+    let gate = Arc::new(Mutex::new(()));
+    let held = gate.lock().unwrap();
+    let kc = slow_bindings(&gate);
+    let mut tasks = TaskTracker::new(DispatchPolicy::Parallel);
+    for _ in 0..3 {
+        assert_eq!(tasks.dispatch(&kc, "slow"), Some(Dispatched::Started));
+    }
+    assert_eq!(tasks.in_flight("slow"), 3);
+    assert_eq!(tasks.queued("slow"), 0);
+    drop(held);
+    assert_eq!(results(tasks.join_all()), vec![1, 2, 3]);
+    assert_eq!(tasks.in_flight("slow"), 0);
+}
+
+#[test]
+fn coalesce_drops_dispatches_while_running() {
+    let gate = Arc::new(Mutex::new(()));
+    let held = gate.lock().unwrap();
+    let kc = slow_bindings(&gate);
+    let mut tasks = TaskTracker::new(DispatchPolicy::Coalesce);
+    assert_eq!(tasks.dispatch(&kc, "slow"), Some(Dispatched::Started));
+    assert_eq!(tasks.dispatch(&kc, "slow"), Some(Dispatched::Coalesced));
+    assert_eq!(tasks.dispatch(&kc, "slow"), Some(Dispatched::Coalesced));
+    assert_eq!(tasks.in_flight("slow"), 1);
+    drop(held);
+    assert_eq!(results(tasks.join_all()), vec![1]);
+    // Once the task is done, the event can be dispatched
+    // again.
+    assert_eq!(tasks.dispatch(&kc, "slow"), Some(Dispatched::Started));
+    assert_eq!(results(tasks.join_all()), vec![2]);
+}
+
+#[test]
+fn queue_runs_dispatches_in_turn() {
+    let gate = Arc::new(Mutex::new(()));
+    let held = gate.lock().unwrap();
+    let kc = slow_bindings(&gate);
+    let mut tasks = TaskTracker::new(DispatchPolicy::Queue);
+    assert_eq!(tasks.dispatch(&kc, "slow"), Some(Dispatched::Started));
+    assert_eq!(tasks.dispatch(&kc, "slow"), Some(Dispatched::Queued));
+    assert_eq!(tasks.dispatch(&kc, "slow"), Some(Dispatched::Queued));
+    assert_eq!(tasks.in_flight("slow"), 1);
+    assert_eq!(tasks.queued("slow"), 2);
+    // Nothing finishes while the gate is held.
+    assert!(tasks.reap().is_empty());
+    assert_eq!(tasks.queued("slow"), 2);
+    drop(held);
+    assert_eq!(results(tasks.join_all()), vec![1, 2, 3]);
+    assert_eq!(tasks.queued("slow"), 0);
+}
+
+#[test]
+fn reap_starts_queued_tasks() {
+    let kc = slow_bindings(&Arc::new(Mutex::new(())));
+    let mut tasks = TaskTracker::new(DispatchPolicy::Queue);
+    tasks.dispatch(&kc, "slow");
+    tasks.dispatch(&kc, "slow");
+    let mut finished = Vec::new();
+    while finished.len() < 2 {
+        finished.extend(tasks.reap());
+        thread::yield_now();
+    }
+    assert_eq!(results(finished), vec![1, 2]);
+    assert_eq!(tasks.in_flight("slow"), 0);
+    assert_eq!(tasks.queued("slow"), 0);
+}
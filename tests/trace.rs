@@ -0,0 +1,86 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Check the spans of `Bindings::run_action_traced()` with
+//! a subscriber that remembers span fields.
+
+#![cfg(feature = "tracing")]
+
+extern crate kbehdz;
+extern crate tracing;
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+use kbehdz::Bindings;
+
+/// Fields of each span, by span name, in creation order.
+type Spans = Arc<Mutex<Vec<(String, HashMap<String, String>)>>>;
+
+/// A subscriber recording the fields of spans as text.
+struct Recorder(Spans);
+
+/// Records fields into a span's field map.
+struct Fields<'f>(&'f mut HashMap<String, String>);
+
+impl<'f> Visit for Fields<'f> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes) -> Id {
+        let mut spans = self.0.lock().unwrap();
+        let mut fields = HashMap::new();
+        span.record(&mut Fields(&mut fields));
+        spans.push((span.metadata().name().to_string(), fields));
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, span: &Id, values: &Record) {
+        let mut spans = self.0.lock().unwrap();
+        let index = span.into_u64() as usize - 1;
+        values.record(&mut Fields(&mut spans[index].1));
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event) {}
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn span_carries_event_and_hit() {
+    let spans: Spans = Arc::default();
+    let one = || 1;
+    let mut kc = Bindings::new();
+    kc.bind_action("a", &one);
+    tracing::subscriber::with_default(Recorder(Arc::clone(&spans)), || {
+        assert_eq!(kc.run_action_traced("a"), Some(1));
+        assert!(kc.run_action_traced("b").is_none());
+    });
+    let spans = spans.lock().unwrap();
+    assert_eq!(spans.len(), 2);
+    for (name, _) in spans.iter() {
+        assert_eq!(name, "run_action");
+    }
+    assert_eq!(spans[0].1["event"], "\"a\"");
+    assert_eq!(spans[0].1["hit"], "true");
+    assert_eq!(spans[1].1["event"], "\"b\"");
+    assert_eq!(spans[1].1["hit"], "false");
+}
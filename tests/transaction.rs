@@ -0,0 +1,126 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Check that a `Transaction` with a bad entry reports
+//! exactly that entry and leaves the bindings untouched.
+
+extern crate kbehdz;
+
+use kbehdz::{ActionRegistry, Bindings, Problem, ProblemKind};
+
+/// Parse a one-character event name.
+fn parse(raw: &str) -> Result<char, String> {
+    let mut chars = raw.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(format!("{:?} is not one character", raw)),
+    }
+}
+
+/// Run a transaction of 80 entries binding events `'0'`
+/// upward, with `bad` added as entry 40, and check that it
+/// fails with `expected` and changes nothing.
+fn check_one_bad<F>(bad: F, expected: ProblemKind<char>)
+    where F: for<'t, 'a> FnOnce(&mut kbehdz::Transaction<'t, 'a, char, u32>)
+{
+    let old = || 0;
+    let new = || 1;
+    let mut kc: Bindings<char, u32> = Bindings::new();
+    kc.bind_action(&'0', &old);
+    kc.bind_alias(&'a', &'0').unwrap();
+    {
+        let mut t = kc.transaction();
+        t.reserve(&'!');
+        t.unbind_action(&'0');
+        t.unbind_alias(&'a');
+        for i in 0..37 {
+            t.bind_action(&((b'0' + i) as char), &new);
+        }
+        t.bind_alias(&'}', &'0');
+        bad(&mut t);
+        for i in 37..76 {
+            t.bind_action(&((b'0' + i) as char), &new);
+        }
+        assert_eq!(t.len(), 80);
+        assert_eq!(t.commit(), Err(vec![Problem { index: 40, kind: expected }]));
+    }
+    assert_eq!(kc.len(), 1);
+    assert_eq!(kc.run_action(&'0'), Some(0));
+    assert_eq!(kc.run_action(&'a'), Some(0));
+    assert!(kc.run_action(&'}').is_none());
+}
+
+#[test]
+fn reserved() {
+    check_one_bad(|t| t.bind_action(&'!', &|| 2), ProblemKind::Reserved('!'));
+}
+
+#[test]
+fn duplicate() {
+    check_one_bad(|t| t.bind_action(&'5', &|| 2), ProblemKind::Duplicate('5'));
+}
+
+#[test]
+fn parse_error() {
+    check_one_bad(
+        |t| t.try_bind("xy", parse, &|| 2),
+        ProblemKind::Parse("\"xy\" is not one character".to_string()),
+    );
+}
+
+#[test]
+fn alias_loop() {
+    check_one_bad(|t| t.bind_alias(&'|', &'|'), ProblemKind::AliasLoop('|'));
+}
+
+#[test]
+fn every_problem_reported() {
+    let act = || ();
+    let mut kc: Bindings<char, ()> = Bindings::new();
+    let mut t = kc.transaction();
+    t.reserve(&'r');
+    t.bind_action(&'r', &act);
+    t.bind_action(&'x', &act);
+    t.bind_action(&'x', &act);
+    t.try_bind("", parse, &act);
+    let indices: Vec<usize> = t.validate().unwrap_err().iter().map(|p| p.index).collect();
+    assert_eq!(indices, vec![0, 2, 3]);
+}
+
+#[test]
+fn named_entry_parsed_first() {
+    let act = || 1;
+    let mut registry = ActionRegistry::new();
+    registry.register("act", &act);
+    let mut kc: Bindings<char, u32> = Bindings::new();
+    {
+        let mut t = kc.transaction();
+        t.try_bind_named("xy", parse, "nope", &registry);
+        t.try_bind_named("y", parse, "nope", &registry);
+        t.try_bind_named("z", parse, "act", &registry);
+        let kinds: Vec<ProblemKind<char>> =
+            t.validate().unwrap_err().into_iter().map(|p| p.kind).collect();
+        assert_eq!(kinds, vec![
+            ProblemKind::Parse("\"xy\" is not one character".to_string()),
+            ProblemKind::UnknownAction("nope".to_string()),
+        ]);
+    }
+    let mut t = kc.transaction();
+    t.try_bind_named("z", parse, "act", &registry);
+    assert!(t.commit().is_ok());
+    assert_eq!(kc.run_action(&'z'), Some(1));
+}
+
+#[test]
+fn dropped_transaction_discarded() {
+    let act = || ();
+    let mut kc: Bindings<char, ()> = Bindings::new();
+    {
+        let mut t = kc.transaction();
+        t.bind_action(&'x', &act);
+        assert!(t.validate().is_ok());
+    }
+    assert!(kc.is_empty());
+}
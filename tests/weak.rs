@@ -0,0 +1,51 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Weak bindings whose actions are dropped between binding
+//! and dispatch.
+
+extern crate kbehdz;
+
+use std::rc::Rc;
+
+use kbehdz::WeakBindings;
+
+#[test]
+fn dropped_action_is_unbound() {
+    let mut kc = WeakBindings::new();
+    {
+        let action: Rc<dyn Fn() -> u32> = Rc::new(|| 1);
+        kc.bind_weak(&'a', Rc::downgrade(&action));
+    }
+    assert!(kc.get_action(&'a').is_none());
+    assert!(kc.run_action(&'a').is_none());
+    assert_eq!(kc.len(), 1);
+    assert_eq!(kc.live_len(), 0);
+}
+
+#[test]
+fn mut_dispatch_removes_dead_binding() {
+    let live: Rc<dyn Fn() -> u32> = Rc::new(|| 1);
+    let dead: Rc<dyn Fn() -> u32> = Rc::new(|| 2);
+    let mut kc = WeakBindings::new();
+    kc.bind_weak(&'l', Rc::downgrade(&live));
+    kc.bind_weak(&'d', Rc::downgrade(&dead));
+    drop(dead);
+    assert_eq!(kc.run_action_mut(&'l'), Some(1));
+    assert!(kc.run_action_mut(&'d').is_none());
+    assert_eq!(kc.len(), 1);
+    assert_eq!(kc.live_len(), 1);
+    assert_eq!(kc.prune(), 0);
+}
+
+#[test]
+fn unbinding_dead_action_returns_none() {
+    let action: Rc<dyn Fn() -> u32> = Rc::new(|| 1);
+    let mut kc = WeakBindings::new();
+    kc.bind_weak(&'a', Rc::downgrade(&action));
+    drop(action);
+    assert!(kc.unbind_action(&'a').is_none());
+    assert!(kc.is_empty());
+}
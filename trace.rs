@@ -0,0 +1,50 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Dispatch spans for the `tracing` ecosystem.
+//!
+//! Tracing is opt-in per call: the `tracing` feature adds
+//! `run_action_traced()`, and leaves `run_action()` and
+//! the other dispatch methods untraced. Recording the
+//! event in a span needs `Debug` events, and since Cargo
+//! features are unified across a build, adding that bound
+//! to `run_action()` whenever the feature is on would break
+//! crates in the same build whose events are not `Debug`.
+
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use tracing::{field, info_span};
+
+use Bindings;
+
+impl<'a, E, R> Bindings<'a, E, R>
+    where E: Hash + Eq, R: 'a
+{
+    /// Like `run_action()`, but within an `info` span named
+    /// `run_action` with an `event` field holding the event
+    /// and a `hit` field saying whether an action ran.
+    ///
+    /// # Examples:
+    ///
+    /// ```
+    /// use kbehdz::Bindings;
+    /// let one = || 1;
+    /// let mut kc = Bindings::new();
+    /// kc.bind_action("a", &one);
+    /// assert_eq!(kc.run_action_traced("a"), Some(1));
+    /// assert!(kc.run_action_traced("b").is_none());
+    /// ```
+    pub fn run_action_traced<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + Debug + ?Sized
+    {
+        let span = info_span!("run_action", event = ?event, hit = field::Empty);
+        let _entered = span.enter();
+        let result = self.run_action(event);
+        span.record("hit", result.is_some());
+        result
+    }
+}
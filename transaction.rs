@@ -0,0 +1,282 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Changing many bindings at once, all or nothing, as when
+//! applying a user profile.
+
+use std::borrow::{Borrow, ToOwned};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+use {alias_loops, Action, ActionRegistry, Bindings};
+
+/// What is wrong with an entry of a `Transaction`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProblemKind<E> {
+    /// No action is registered under this name.
+    UnknownAction(String),
+    /// The event is reserved.
+    Reserved(E),
+    /// The event is bound by an earlier entry.
+    Duplicate(E),
+    /// The event text did not parse, with this message.
+    Parse(String),
+    /// Making this event an alias would loop.
+    AliasLoop(E),
+}
+
+/// A problem with the entry numbered `index` of a
+/// `Transaction`, counting from 0 in the order the entries
+/// were added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem<E> {
+    /// Number of the offending entry.
+    pub index: usize,
+    /// What is wrong with the entry.
+    pub kind: ProblemKind<E>,
+}
+
+impl<E: Debug> Display for Problem<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "entry {}: ", self.index)?;
+        match self.kind {
+            ProblemKind::UnknownAction(ref name) => write!(f, "unknown action {:?}", name),
+            ProblemKind::Reserved(ref event) => write!(f, "{:?} is reserved", event),
+            ProblemKind::Duplicate(ref event) => write!(f, "{:?} is already bound", event),
+            ProblemKind::Parse(ref message) => write!(f, "parse error: {}", message),
+            ProblemKind::AliasLoop(ref event) => write!(f, "alias {:?} would loop", event),
+        }
+    }
+}
+
+/// An entry of a `Transaction`.
+enum Op<'a, E, R: 'a> {
+    Bind(E, Action<'a, R>),
+    Unbind(E),
+    Alias(E, E),
+    Unalias(E),
+    Invalid(ProblemKind<E>),
+}
+
+/// A `Transaction` object collects changes to a `Bindings`,
+/// applying them in order when committed, and only if none
+/// of them has a problem. Dropping a transaction without
+/// committing it discards its changes.
+///
+/// # Examples:
+///
+/// ```
+/// use kbehdz::{ActionRegistry, Bindings, ProblemKind};
+/// let save = || "save";
+/// let quit = || "quit";
+/// let mut registry = ActionRegistry::new();
+/// registry.register("save", &save);
+/// registry.register("quit", &quit);
+/// let mut kc: Bindings<String, &str> = Bindings::new();
+/// kc.bind_action("C-q", &quit);
+///
+/// let mut profile = kc.transaction();
+/// profile.unbind_action("C-q");
+/// profile.bind_named("C-s", "save", &registry);
+/// profile.bind_named("C-x", "exit", &registry);
+/// profile.bind_named("C-w", "quit", &registry);
+/// let problems = profile.commit().unwrap_err();
+/// assert_eq!(problems.len(), 1);
+/// assert_eq!(problems[0].index, 2);
+/// assert_eq!(problems[0].kind, ProblemKind::UnknownAction("exit".to_string()));
+/// assert_eq!(kc.run_action("C-q"), Some("quit"));
+/// assert!(kc.run_action("C-s").is_none());
+///
+/// let mut profile = kc.transaction();
+/// profile.unbind_action("C-q");
+/// profile.bind_named("C-s", "save", &registry);
+/// profile.bind_named("C-w", "quit", &registry);
+/// profile.bind_alias("C-x", "C-w");
+/// assert!(profile.commit().is_ok());
+/// assert!(kc.run_action("C-q").is_none());
+/// assert_eq!(kc.run_action("C-s"), Some("save"));
+/// assert_eq!(kc.run_action("C-x"), Some("quit"));
+/// ```
+pub struct Transaction<'t, 'a, E, R>
+    where E: Hash + Eq + 'a, R: 'a, 'a: 't
+{
+    bindings: &'t mut Bindings<'a, E, R>,
+    ops: Vec<Op<'a, E, R>>,
+    reserved: HashSet<E>,
+}
+
+impl<'a, E, R> Bindings<'a, E, R>
+    where E: Hash + Eq + Clone, R: 'a
+{
+    /// Start a transaction changing these bindings.
+    pub fn transaction(&mut self) -> Transaction<'_, 'a, E, R> {
+        Transaction {
+            bindings: self,
+            ops: Vec::new(),
+            reserved: HashSet::new(),
+        }
+    }
+}
+
+impl<'t, 'a, E, R> Transaction<'t, 'a, E, R>
+    where E: Hash + Eq + Clone, R: 'a
+{
+    /// Refuse entries that bind, unbind or alias `event`.
+    pub fn reserve<T>(&mut self, event: &T)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.reserved.insert(event.to_owned());
+    }
+
+    /// Add an entry binding `event` to `action`.
+    pub fn bind_action<T>(&mut self, event: &T, action: Action<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.ops.push(Op::Bind(event.to_owned(), action));
+    }
+
+    /// Add an entry binding `event` to the action
+    /// registered under `name` in `registry`.
+    pub fn bind_named<T>(&mut self, event: &T, name: &str, registry: &ActionRegistry<'a, R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.ops.push(match registry.get(name) {
+            Some(action) => Op::Bind(event.to_owned(), action),
+            None => Op::Invalid(ProblemKind::UnknownAction(name.to_owned())),
+        });
+    }
+
+    /// Add an entry binding the event `parse` makes of
+    /// `raw` to `action`.
+    pub fn try_bind<T, F, X>(&mut self, raw: &T, parse: F, action: Action<'a, R>)
+        where T: ?Sized, F: FnOnce(&T) -> Result<E, X>, X: Display
+    {
+        self.ops.push(match parse(raw) {
+            Ok(event) => Op::Bind(event, action),
+            Err(e) => Op::Invalid(ProblemKind::Parse(e.to_string())),
+        });
+    }
+
+    /// Add an entry binding the event `parse` makes of
+    /// `raw` to the action registered under `name` in
+    /// `registry`. If `raw` does not parse, that is the
+    /// problem reported, whatever `name` is.
+    pub fn try_bind_named<T, F, X>(&mut self,
+                                   raw: &T,
+                                   parse: F,
+                                   name: &str,
+                                   registry: &ActionRegistry<'a, R>)
+        where T: ?Sized, F: FnOnce(&T) -> Result<E, X>, X: Display
+    {
+        self.ops.push(match (parse(raw), registry.get(name)) {
+            (Err(e), _) => Op::Invalid(ProblemKind::Parse(e.to_string())),
+            (Ok(_), None) => Op::Invalid(ProblemKind::UnknownAction(name.to_owned())),
+            (Ok(event), Some(action)) => Op::Bind(event, action),
+        });
+    }
+
+    /// Add an entry removing the binding for `event`.
+    pub fn unbind_action<T>(&mut self, event: &T)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.ops.push(Op::Unbind(event.to_owned()));
+    }
+
+    /// Add an entry making `alias` a synonym for `target`.
+    pub fn bind_alias<T>(&mut self, alias: &T, target: &T)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.ops.push(Op::Alias(alias.to_owned(), target.to_owned()));
+    }
+
+    /// Add an entry removing the alias `alias`.
+    pub fn unbind_alias<T>(&mut self, alias: &T)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.ops.push(Op::Unalias(alias.to_owned()));
+    }
+
+    /// Number of entries added.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// True if no entries have been added.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Check every entry, returning all the problems found,
+    /// in entry order. Binding or aliasing an event that an
+    /// earlier entry bound or aliased is a problem.
+    pub fn validate(&self) -> Result<(), Vec<Problem<E>>> {
+        let mut problems = Vec::new();
+        let mut bound: HashSet<&E> = HashSet::new();
+        let mut aliases: HashMap<E, E> = self.bindings.aliases.clone();
+        for (index, op) in self.ops.iter().enumerate() {
+            let kind = match *op {
+                Op::Invalid(ref kind) => Some(kind.clone()),
+                Op::Bind(ref event, _) | Op::Alias(ref event, _)
+                    if self.reserved.contains(event) =>
+                {
+                    Some(ProblemKind::Reserved(event.clone()))
+                },
+                Op::Unbind(ref event) | Op::Unalias(ref event)
+                    if self.reserved.contains(event) =>
+                {
+                    Some(ProblemKind::Reserved(event.clone()))
+                },
+                Op::Bind(ref event, _) | Op::Alias(ref event, _) if !bound.insert(event) => {
+                    Some(ProblemKind::Duplicate(event.clone()))
+                },
+                Op::Alias(ref alias, ref target) => {
+                    if alias_loops::<E, E>(&aliases, alias, target) {
+                        Some(ProblemKind::AliasLoop(alias.clone()))
+                    } else {
+                        aliases.insert(alias.clone(), target.clone());
+                        None
+                    }
+                },
+                Op::Unalias(ref alias) => {
+                    aliases.remove(alias);
+                    None
+                },
+                Op::Bind(..) | Op::Unbind(_) => None,
+            };
+            if let Some(kind) = kind {
+                problems.push(Problem { index, kind });
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Apply every entry, in order, if `validate()` finds no
+    /// problems. Otherwise change nothing and return the
+    /// problems.
+    pub fn commit(self) -> Result<(), Vec<Problem<E>>> {
+        self.validate()?;
+        for op in self.ops {
+            match op {
+                Op::Bind(event, action) => self.bindings.insert(event, action),
+                Op::Unbind(event) => {
+                    self.bindings.store.remove::<E>(&event);
+                },
+                Op::Alias(alias, target) => {
+                    self.bindings.aliases.insert(alias, target);
+                },
+                Op::Unalias(alias) => {
+                    self.bindings.aliases.remove(&alias);
+                },
+                Op::Invalid(_) => unreachable!("validated transaction has no invalid entries"),
+            }
+        }
+        Ok(())
+    }
+}
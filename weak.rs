@@ -0,0 +1,123 @@
+// Copyright © 2018 Bart Massey
+// [This program is licensed under the "MIT License"]
+// Please see the file LICENSE in the source
+// distribution of this software for license terms.
+
+//! Bindings that do not keep their actions alive, for
+//! plugins and other owners whose bindings should stop
+//! working when they go away.
+
+use std::borrow::{Borrow, ToOwned};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::{Rc, Weak};
+
+/// A `WeakBindings` object binds events to weak references
+/// to actions. Once every `Rc` to an action is dropped, its
+/// events act as unbound; `prune()` removes them.
+///
+/// # Examples:
+///
+/// ```
+/// use std::rc::Rc;
+/// use kbehdz::WeakBindings;
+/// let plugin: Rc<dyn Fn() -> u32> = Rc::new(|| 7);
+/// let mut kc = WeakBindings::new();
+/// kc.bind_weak("p", Rc::downgrade(&plugin));
+/// assert_eq!(kc.run_action("p"), Some(7));
+/// drop(plugin);
+/// assert!(kc.run_action("p").is_none());
+/// assert_eq!((kc.len(), kc.live_len()), (1, 0));
+/// assert_eq!(kc.prune(), 1);
+/// assert!(kc.is_empty());
+/// ```
+pub struct WeakBindings<E, R>(HashMap<E, Weak<dyn Fn() -> R>>)
+    where E: Hash + Eq;
+
+impl<E, R> Default for WeakBindings<E, R>
+    where E: Hash + Eq
+{
+    fn default() -> Self {
+        WeakBindings::new()
+    }
+}
+
+impl<E, R> WeakBindings<E, R>
+    where E: Hash + Eq
+{
+    /// Make a new empty binding.
+    pub fn new() -> Self {
+        WeakBindings(HashMap::new())
+    }
+
+    /// Overwrite or create a binding to a weakly held
+    /// action. The event must be passed by reference: it
+    /// will be converted to an owned type.
+    pub fn bind_weak<T>(&mut self, event: &T, action: Weak<dyn Fn() -> R>)
+        where E: Borrow<T>, T: ToOwned<Owned=E> + ?Sized
+    {
+        self.0.insert(event.to_owned(), action);
+    }
+
+    /// Remove the binding for `event`, returning its action
+    /// if it is still alive.
+    pub fn unbind_action<T>(&mut self, event: &T) -> Option<Rc<dyn Fn() -> R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.0.remove(event).and_then(|action| action.upgrade())
+    }
+
+    /// Given an event that is bound to a live action, run
+    /// the action and return the result. Return `None` if
+    /// no such event is bound or its action is gone.
+    pub fn run_action<T>(&self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.get_action(event).map(|action| action())
+    }
+
+    /// Given an event that is bound to a live action,
+    /// return the action unexecuted. Return `None` if no
+    /// such event is bound or its action is gone.
+    pub fn get_action<T>(&self, event: &T) -> Option<Rc<dyn Fn() -> R>>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        self.0.get(event).and_then(|action| action.upgrade())
+    }
+
+    /// As `run_action()`, but also remove the binding if its
+    /// action is gone.
+    pub fn run_action_mut<T>(&mut self, event: &T) -> Option<R>
+        where E: Borrow<T>, T: Hash + Eq + ?Sized
+    {
+        let action = self.0.get(event)?.upgrade();
+        if action.is_none() {
+            self.0.remove(event);
+        }
+        action.map(|action| action())
+    }
+
+    /// Remove the bindings whose actions are gone, returning
+    /// how many were removed.
+    pub fn prune(&mut self) -> usize {
+        let before = self.0.len();
+        self.0.retain(|_, action| action.strong_count() > 0);
+        before - self.0.len()
+    }
+
+    /// Number of bindings, including those whose actions
+    /// are gone.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// True if there are no bindings, live or not.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Number of bindings whose actions are still alive.
+    pub fn live_len(&self) -> usize {
+        self.0.values().filter(|action| action.strong_count() > 0).count()
+    }
+}